@@ -78,7 +78,7 @@ fn execute_cmd<E: Engine>(opts: Phase1Opts) {
         }
         Command::VerifyAndTransformPokAndCorrectness(opt) => {
             // we receive a previous participation, verify it, and generate a new challenge from it
-            transform_pok_and_correctness(
+            if let Err(e) = transform_pok_and_correctness(
                 CHALLENGE_IS_COMPRESSED,
                 &opt.challenge_fname,
                 CONTRIBUTION_IS_COMPRESSED,
@@ -86,7 +86,10 @@ fn execute_cmd<E: Engine>(opts: Phase1Opts) {
                 CHALLENGE_IS_COMPRESSED,
                 &opt.new_challenge_fname,
                 &parameters,
-            );
+            ) {
+                eprintln!("INVALID CONTRIBUTION!!! {}", e);
+                process::exit(1)
+            }
         }
         Command::VerifyAndTransformRatios(opt) => {
             // we receive a previous participation, verify it, and generate a new challenge from it
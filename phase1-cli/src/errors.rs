@@ -0,0 +1,27 @@
+use std::io;
+use thiserror::Error;
+
+/// Errors that might occur while verifying and transforming a contribution.
+#[derive(Debug, Error)]
+pub enum Phase1CliError {
+    #[error("Disk IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("The size of the {kind} file should be {expected}, but it's {got}, so something isn't right.")]
+    InvalidFileSize {
+        kind: &'static str,
+        expected: u64,
+        got: u64,
+    },
+    #[error(
+        "Hash chain failure. This is not the right response: it is based on {}, but the challenge hash is {}",
+        hex::encode(&.found[..]),
+        hex::encode(&.expected[..])
+    )]
+    HashChainMismatch { expected: [u8; 64], found: [u8; 64] },
+    #[error("Streaming verification is not supported: {0}")]
+    StreamingUnsupported(&'static str),
+    #[error("Invalid contribution: {0}")]
+    InvalidContribution(setup_utils::Error),
+    #[error("Setup error: {0}")]
+    SetupError(#[from] setup_utils::Error),
+}
@@ -1,6 +1,9 @@
 // Documentation
 #![doc = include_str!("../README.md")]
 
+mod errors;
+pub use errors::Phase1CliError;
+
 mod combine;
 pub use combine::combine;
 
@@ -11,7 +14,7 @@ mod new_challenge;
 pub use new_challenge::new_challenge;
 
 mod transform_pok_and_correctness;
-pub use transform_pok_and_correctness::transform_pok_and_correctness;
+pub use transform_pok_and_correctness::{transform_pok_and_correctness, verify_response_streaming, verify_responses};
 
 mod transform_ratios;
 pub use transform_ratios::transform_ratios;
@@ -1,12 +1,22 @@
-use phase1::{Phase1, Phase1Parameters, PublicKey};
-use setup_utils::{calculate_hash, print_hash, CheckForCorrectness, UseCompression};
+use crate::Phase1CliError;
+use phase1::{ContributionMode, Phase1, Phase1Parameters, ProvingSystem, PublicKey};
+use setup_utils::{
+    buffer_size,
+    calculate_hash,
+    print_hash,
+    CheckForCorrectness,
+    GenericArray,
+    HashWriter,
+    UseCompression,
+    U64,
+};
 
 use snarkvm_curves::PairingEngine as Engine;
 
 use memmap::*;
 use std::{
     fs::{self, OpenOptions},
-    io::{Read, Write},
+    io::{self, Read, Seek, SeekFrom, Write},
 };
 
 pub fn transform_pok_and_correctness<T: Engine + Sync>(
@@ -17,94 +27,447 @@ pub fn transform_pok_and_correctness<T: Engine + Sync>(
     compress_new_challenge: UseCompression,
     new_challenge_filename: &str,
     parameters: &Phase1Parameters<T>,
-) {
+) -> Result<(), Phase1CliError> {
     println!(
         "Will verify and decompress a contribution to accumulator for 2^{} powers of tau",
         parameters.total_size_in_log2
     );
 
+    let challenge_readable_map = load_challenge(challenge_is_compressed, challenge_filename, parameters)?;
+
+    println!("Calculating previous challenge hash...");
+
+    // Check that contribution is correct
+
+    let current_accumulator_hash = calculate_hash(&challenge_readable_map);
+
+    println!("Hash of the `challenge` file for verification:");
+    print_hash(&current_accumulator_hash);
+
+    let (response_readable_map, response_hash) = verify_response(
+        challenge_is_compressed,
+        &challenge_readable_map,
+        &current_accumulator_hash,
+        contribution_is_compressed,
+        response_filename,
+        parameters,
+    )?;
+
+    if compress_new_challenge == contribution_is_compressed {
+        println!("Don't need to recompress the contribution, copying the file without the public key...");
+        fs::copy(challenge_filename, new_challenge_filename)?;
+        let f = fs::File::open(new_challenge_filename)?;
+        f.set_len((parameters.accumulator_size + parameters.public_key_size) as u64)?;
+
+        let new_challenge_reader = OpenOptions::new().read(true).open(new_challenge_filename)?;
+
+        let new_challenge_readable_map = unsafe { MmapOptions::new().map(&new_challenge_reader)? };
+
+        let hash = calculate_hash(&new_challenge_readable_map);
+
+        println!("Here's the BLAKE2b hash of the decompressed participant's response as new_challenge file:");
+        print_hash(&hash);
+        println!("Done! new challenge file contains the new challenge file. The other files");
+        println!("were left alone.");
+    } else if new_challenge_is_complete(new_challenge_filename, parameters.accumulator_size, &response_hash)? {
+        println!("The new challenge file was already written by a previous run, skipping...");
+
+        let new_challenge_reader = OpenOptions::new().read(true).open(new_challenge_filename)?;
+        let new_challenge_readable_map = unsafe { MmapOptions::new().map(&new_challenge_reader)? };
+
+        let recompressed_hash = calculate_hash(&new_challenge_readable_map);
+
+        println!("Here's the BLAKE2b hash of the decompressed participant's response as new_challenge file:");
+        print_hash(&recompressed_hash);
+        println!("Done! new challenge file contains the new challenge file. The other files");
+        println!("were left alone.");
+    } else {
+        println!("Verification succeeded! Writing to new challenge file...");
+
+        // Create new challenge file in this directory, discarding the output of an interrupted run
+        let writer = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(new_challenge_filename)?;
+
+        // Recomputation strips the public key and uses hashing to link with the previous contribution after decompression
+        writer.set_len(parameters.accumulator_size as u64)?;
+
+        let mut writable_map = unsafe { MmapOptions::new().map_mut(&writer)? };
+
+        Phase1::decompress(
+            &response_readable_map,
+            &mut writable_map,
+            CheckForCorrectness::No,
+            &parameters,
+        )?;
+
+        writable_map.flush()?;
+
+        // The hash is written last, so that a rerun can tell whether this write was interrupted
+        {
+            (&mut writable_map[0..]).write_all(response_hash.as_slice())?;
+
+            writable_map.flush()?;
+        }
+
+        let new_challenge_readable_map = writable_map.make_read_only()?;
+
+        let recompressed_hash = calculate_hash(&new_challenge_readable_map);
+
+        println!("Here's the BLAKE2b hash of the decompressed participant's response as new_challenge file:");
+        print_hash(&recompressed_hash);
+        println!("Done! new challenge file contains the new challenge file. The other files");
+        println!("were left alone.");
+    }
+
+    Ok(())
+}
+
+/// Verifies several responses to the same challenge, e.g. when fanning out a round to
+/// multiple contributors. The challenge is only loaded and hashed once. Returns, in order,
+/// either the hash of each response or the reason why it is invalid.
+pub fn verify_responses<T: Engine + Sync>(
+    challenge_is_compressed: UseCompression,
+    challenge_filename: &str,
+    contribution_is_compressed: UseCompression,
+    response_filenames: &[&str],
+    parameters: &Phase1Parameters<T>,
+) -> Result<Vec<Result<GenericArray<u8, U64>, Phase1CliError>>, Phase1CliError> {
+    println!(
+        "Will verify {} contributions to accumulator for 2^{} powers of tau",
+        response_filenames.len(),
+        parameters.total_size_in_log2
+    );
+
+    let challenge_readable_map = load_challenge(challenge_is_compressed, challenge_filename, parameters)?;
+
+    println!("Calculating previous challenge hash...");
+
+    let current_accumulator_hash = calculate_hash(&challenge_readable_map);
+
+    println!("Hash of the `challenge` file for verification:");
+    print_hash(&current_accumulator_hash);
+
+    let results = response_filenames
+        .iter()
+        .map(|response_filename| {
+            println!("Verifying {}...", response_filename);
+            verify_response(
+                challenge_is_compressed,
+                &challenge_readable_map,
+                &current_accumulator_hash,
+                contribution_is_compressed,
+                response_filename,
+                parameters,
+            )
+            .map(|(_, response_hash)| response_hash)
+        })
+        .collect();
+
+    Ok(results)
+}
+
+/// Same verification as `transform_pok_and_correctness`, but instead of memory mapping the
+/// challenge and response files, they are read in windows of `parameters.batch_size` powers,
+/// each of which is verified as a chunk. This bounds the memory used for very large files,
+/// at the cost of being slower than the memory mapped path. Returns the hash of the response.
+/// Only Groth16 accumulators are supported.
+pub fn verify_response_streaming<T: Engine + Sync>(
+    challenge_is_compressed: UseCompression,
+    challenge_filename: &str,
+    contribution_is_compressed: UseCompression,
+    response_filename: &str,
+    parameters: &Phase1Parameters<T>,
+) -> Result<GenericArray<u8, U64>, Phase1CliError> {
+    println!(
+        "Will verify a contribution to accumulator for 2^{} powers of tau in windows of {} powers",
+        parameters.total_size_in_log2, parameters.batch_size
+    );
+
+    if parameters.proving_system != ProvingSystem::Groth16 {
+        return Err(Phase1CliError::StreamingUnsupported(
+            "only Groth16 accumulators can be verified in windows",
+        ));
+    }
+
+    // The windows are verified as chunks, so they must line up with the chunk of the files
+    let (files_start, window_size) = match parameters.contribution_mode {
+        ContributionMode::Full => (0, parameters.batch_size),
+        ContributionMode::Chunked if parameters.chunk_size % parameters.batch_size == 0 => {
+            (parameters.chunk_index * parameters.chunk_size, parameters.batch_size)
+        }
+        ContributionMode::Chunked => (parameters.chunk_index * parameters.chunk_size, parameters.chunk_size),
+    };
+
+    let mut challenge = open_with_length(
+        challenge_filename,
+        "challenge",
+        parameters.get_length(challenge_is_compressed),
+    )?;
+    let response_length = match contribution_is_compressed {
+        UseCompression::Yes => parameters.contribution_size,
+        UseCompression::No => parameters.accumulator_size + parameters.public_key_size,
+    };
+    let mut response = open_with_length(response_filename, "response", response_length)?;
+
+    println!("Calculating previous challenge hash...");
+
+    let current_accumulator_hash = hash_file(&mut challenge)?;
+
+    println!("Hash of the `challenge` file for verification:");
+    print_hash(&current_accumulator_hash);
+
+    // Check the hash chain - a new response must be based on the previous challenge!
+    {
+        let mut response_challenge_hash = [0; 64];
+        response.seek(SeekFrom::Start(0))?;
+        response.read_exact(&mut response_challenge_hash)?;
+
+        println!("`response` was based on the hash:");
+        print_hash(&response_challenge_hash);
+
+        if &response_challenge_hash[..] != current_accumulator_hash.as_slice() {
+            let mut expected = [0; 64];
+            expected.copy_from_slice(current_accumulator_hash.as_slice());
+            return Err(Phase1CliError::HashChainMismatch {
+                expected,
+                found: response_challenge_hash,
+            });
+        }
+    }
+
+    let response_hash = hash_file(&mut response)?;
+
+    println!("Hash of the response file for verification:");
+    print_hash(&response_hash);
+
+    let first_window = files_start / window_size;
+    let last_window = (files_start + parameters.g1_chunk_size + window_size - 1) / window_size;
+
+    // get the contributor's public key, which follows the accumulator
+    let public_key = {
+        let window = parameters.into_chunk_parameters(ContributionMode::Chunked, first_window, window_size);
+        let mut buffer = read_window(
+            &mut response,
+            files_start,
+            contribution_is_compressed,
+            parameters,
+            &window,
+        )?;
+        read_range(
+            &mut response,
+            parameters.get_length(contribution_is_compressed),
+            parameters.public_key_size,
+            &mut buffer,
+        )?;
+        PublicKey::read(&buffer, contribution_is_compressed, &window)?
+    };
+
+    println!("Verifying a contribution to contain proper powers and correspond to the public key...");
+
+    for index in first_window..last_window {
+        let window = parameters.into_chunk_parameters(ContributionMode::Chunked, index, window_size);
+        let challenge_window = read_window(
+            &mut challenge,
+            files_start,
+            challenge_is_compressed,
+            parameters,
+            &window,
+        )?;
+        let response_window = read_window(
+            &mut response,
+            files_start,
+            contribution_is_compressed,
+            parameters,
+            &window,
+        )?;
+
+        Phase1::verification(
+            &challenge_window,
+            &response_window,
+            &public_key,
+            current_accumulator_hash.as_slice(),
+            challenge_is_compressed,
+            contribution_is_compressed,
+            CheckForCorrectness::No,
+            CheckForCorrectness::Full,
+            &window,
+        )
+        .map_err(Phase1CliError::InvalidContribution)?;
+    }
+
+    println!("Verification succeeded!");
+
+    Ok(response_hash)
+}
+
+/// Opens a file, checking that it has the `expected` length.
+fn open_with_length(filename: &str, kind: &'static str, expected: usize) -> Result<fs::File, Phase1CliError> {
+    let file = OpenOptions::new().read(true).open(filename)?;
+    let got = file.metadata()?.len();
+    if got != expected as u64 {
+        return Err(Phase1CliError::InvalidFileSize {
+            kind,
+            expected: expected as u64,
+            got,
+        });
+    }
+    Ok(file)
+}
+
+/// Calculates the hash of a file without loading it in memory at once.
+fn hash_file(file: &mut fs::File) -> Result<GenericArray<u8, U64>, Phase1CliError> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut hasher = HashWriter::new(io::sink());
+    io::copy(file, &mut hasher)?;
+    Ok(hasher.into_hash())
+}
+
+/// Appends `len` bytes of the file starting at `start` to the buffer.
+fn read_range(file: &mut fs::File, start: usize, len: usize, buffer: &mut Vec<u8>) -> Result<(), Phase1CliError> {
+    let end = buffer.len();
+    buffer.resize(end + len, 0);
+    file.seek(SeekFrom::Start(start as u64))?;
+    file.read_exact(&mut buffer[end..])?;
+    Ok(())
+}
+
+/// Reads the part of an accumulator file laid out for `parameters`, whose powers start
+/// at `files_start`, that corresponds to the chunk `window`. The result is laid out as the
+/// accumulator of that chunk: the hash, the powers of the chunk and beta in G2.
+fn read_window<T: Engine>(
+    file: &mut fs::File,
+    files_start: usize,
+    compressed: UseCompression,
+    parameters: &Phase1Parameters<T>,
+    window: &Phase1Parameters<T>,
+) -> Result<Vec<u8>, Phase1CliError> {
+    let g1_size = buffer_size::<T::G1Affine>(compressed);
+    let g2_size = buffer_size::<T::G2Affine>(compressed);
+    let offset = window.chunk_index * window.chunk_size - files_start;
+
+    let mut buffer = Vec::with_capacity(window.get_length(compressed));
+    read_range(file, 0, parameters.hash_size, &mut buffer)?;
+
+    // tau_g1, tau_g2, alpha_g1 and beta_g1, then beta_g2
+    let mut position = parameters.hash_size;
+    for &(element_size, len, window_len) in &[
+        (g1_size, parameters.g1_chunk_size, window.g1_chunk_size),
+        (g2_size, parameters.other_chunk_size, window.other_chunk_size),
+        (g1_size, parameters.other_chunk_size, window.other_chunk_size),
+        (g1_size, parameters.other_chunk_size, window.other_chunk_size),
+    ] {
+        if window_len > 0 {
+            read_range(
+                file,
+                position + offset * element_size,
+                window_len * element_size,
+                &mut buffer,
+            )?;
+        }
+        position += len * element_size;
+    }
+    read_range(file, position, g2_size, &mut buffer)?;
+
+    Ok(buffer)
+}
+
+fn load_challenge<T: Engine + Sync>(
+    challenge_is_compressed: UseCompression,
+    challenge_filename: &str,
+    parameters: &Phase1Parameters<T>,
+) -> Result<Mmap, Phase1CliError> {
     // Try to load challenge file from disk.
-    let challenge_reader = OpenOptions::new()
-        .read(true)
-        .open(challenge_filename)
-        .expect("unable open challenge file in this directory");
+    let challenge_reader = OpenOptions::new().read(true).open(challenge_filename)?;
 
     {
-        let metadata = challenge_reader
-            .metadata()
-            .expect("unable to get filesystem metadata for challenge file");
+        let metadata = challenge_reader.metadata()?;
         let expected_challenge_length = match challenge_is_compressed {
             UseCompression::Yes => parameters.contribution_size - parameters.public_key_size,
             UseCompression::No => parameters.accumulator_size,
         };
         if metadata.len() != (expected_challenge_length as u64) {
-            panic!(
-                "The size of challenge file should be {}, but it's {}, so something isn't right.",
-                expected_challenge_length,
-                metadata.len()
-            );
+            return Err(Phase1CliError::InvalidFileSize {
+                kind: "challenge",
+                expected: expected_challenge_length as u64,
+                got: metadata.len(),
+            });
         }
     }
 
-    let challenge_readable_map = unsafe {
-        MmapOptions::new()
-            .map(&challenge_reader)
-            .expect("unable to create a memory map for input")
+    let challenge_readable_map = unsafe { MmapOptions::new().map(&challenge_reader)? };
+    Ok(challenge_readable_map)
+}
+
+/// Checks whether a previous run completely wrote the new challenge file. Since the response
+/// hash is written last, a file of the right size starting with it is complete.
+fn new_challenge_is_complete(
+    new_challenge_filename: &str,
+    expected_length: usize,
+    response_hash: &GenericArray<u8, U64>,
+) -> Result<bool, Phase1CliError> {
+    let mut new_challenge = match fs::File::open(new_challenge_filename) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
     };
+    if new_challenge.metadata()?.len() != expected_length as u64 {
+        return Ok(false);
+    }
+
+    let mut hash = [0; 64];
+    new_challenge.read_exact(&mut hash)?;
+    Ok(&hash[..] == response_hash.as_slice())
+}
 
+/// Checks that the response is based on the challenge with the provided hash and that it
+/// follows the protocol. Returns the response's memory map along with its hash.
+fn verify_response<T: Engine + Sync>(
+    challenge_is_compressed: UseCompression,
+    challenge_readable_map: &Mmap,
+    current_accumulator_hash: &GenericArray<u8, U64>,
+    contribution_is_compressed: UseCompression,
+    response_filename: &str,
+    parameters: &Phase1Parameters<T>,
+) -> Result<(Mmap, GenericArray<u8, U64>), Phase1CliError> {
     // Try to load response file from disk.
-    let response_reader = OpenOptions::new()
-        .read(true)
-        .open(response_filename)
-        .expect("unable open response file in this directory");
+    let response_reader = OpenOptions::new().read(true).open(response_filename)?;
 
     {
-        let metadata = response_reader
-            .metadata()
-            .expect("unable to get filesystem metadata for response file");
+        let metadata = response_reader.metadata()?;
         let expected_response_length = match contribution_is_compressed {
             UseCompression::Yes => parameters.contribution_size,
             UseCompression::No => parameters.accumulator_size + parameters.public_key_size,
         };
         if metadata.len() != (expected_response_length as u64) {
-            panic!(
-                "The size of response file should be {}, but it's {}, so something isn't right.",
-                expected_response_length,
-                metadata.len()
-            );
+            return Err(Phase1CliError::InvalidFileSize {
+                kind: "response",
+                expected: expected_response_length as u64,
+                got: metadata.len(),
+            });
         }
     }
 
-    let response_readable_map = unsafe {
-        MmapOptions::new()
-            .map(&response_reader)
-            .expect("unable to create a memory map for input")
-    };
-
-    println!("Calculating previous challenge hash...");
-
-    // Check that contribution is correct
-
-    let current_accumulator_hash = calculate_hash(&challenge_readable_map);
-
-    println!("Hash of the `challenge` file for verification:");
-    print_hash(&current_accumulator_hash);
+    let response_readable_map = unsafe { MmapOptions::new().map(&response_reader)? };
 
     // Check the hash chain - a new response must be based on the previous challenge!
     {
         let mut response_challenge_hash = [0; 64];
-        let mut memory_slice = response_readable_map
-            .get(0..64)
-            .expect("must read point data from file");
-        memory_slice
-            .read_exact(&mut response_challenge_hash)
-            .expect("couldn't read hash of challenge file from response file");
+        (&response_readable_map[..]).read_exact(&mut response_challenge_hash)?;
 
         println!("`response` was based on the hash:");
         print_hash(&response_challenge_hash);
 
         if &response_challenge_hash[..] != current_accumulator_hash.as_slice() {
-            panic!("Hash chain failure. This is not the right response.");
+            let mut expected = [0; 64];
+            expected.copy_from_slice(current_accumulator_hash.as_slice());
+            return Err(Phase1CliError::HashChainMismatch {
+                expected,
+                found: response_challenge_hash,
+            });
         }
     }
 
@@ -114,15 +477,14 @@ pub fn transform_pok_and_correctness<T: Engine + Sync>(
     print_hash(&response_hash);
 
     // get the contributor's public key
-    let public_key = PublicKey::read(&response_readable_map, contribution_is_compressed, &parameters)
-        .expect("wasn't able to deserialize the response file's public key");
+    let public_key = PublicKey::read(&response_readable_map, contribution_is_compressed, &parameters)?;
 
     // check that it follows the protocol
 
     println!("Verifying a contribution to contain proper powers and correspond to the public key...");
 
-    let res = Phase1::verification(
-        &challenge_readable_map,
+    Phase1::verification(
+        challenge_readable_map,
         &response_readable_map,
         &public_key,
         current_accumulator_hash.as_slice(),
@@ -131,89 +493,206 @@ pub fn transform_pok_and_correctness<T: Engine + Sync>(
         CheckForCorrectness::No,
         CheckForCorrectness::Full,
         &parameters,
-    );
+    )
+    .map_err(Phase1CliError::InvalidContribution)?;
 
-    if let Err(e) = res {
-        println!("Verification failed: {}", e);
-        panic!("INVALID CONTRIBUTION!!!");
-    } else {
-        println!("Verification succeeded!");
-    }
+    println!("Verification succeeded!");
 
-    if compress_new_challenge == contribution_is_compressed {
-        println!("Don't need to recompress the contribution, copying the file without the public key...");
-        fs::copy(challenge_filename, new_challenge_filename)
-            .expect("Should have been able to copy the new challenge file");
-        let f = fs::File::open(new_challenge_filename).expect("Should have been able to open the new challenge file");
-        f.set_len((parameters.accumulator_size + parameters.public_key_size) as u64)
-            .expect("Should have been able to truncate the new challenge file");
+    Ok((response_readable_map, response_hash))
+}
 
-        let new_challenge_reader = OpenOptions::new()
-            .read(true)
-            .open(new_challenge_filename)
-            .expect("unable open new challenge file in this directory");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{contribute, new_challenge};
 
-        let new_challenge_readable_map = unsafe {
-            MmapOptions::new()
-                .map(&new_challenge_reader)
-                .expect("unable to create a memory map for new input")
-        };
+    use snarkvm_curves::bls12_377::Bls12_377;
 
-        let hash = calculate_hash(&new_challenge_readable_map);
+    use rand::thread_rng;
+    use std::env;
 
-        println!("Here's the BLAKE2b hash of the decompressed participant's response as new_challenge file:");
-        print_hash(&hash);
-        println!("Done! new challenge file contains the new challenge file. The other files");
-        println!("were left alone.");
-    } else {
-        println!("Verification succeeded! Writing to new challenge file...");
+    fn temp_path(name: &str) -> String {
+        let path = env::temp_dir().join(format!("phase1_cli_{}_{}", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path.to_str().unwrap().to_string()
+    }
 
-        // Create new challenge file in this directory
-        let writer = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create_new(true)
-            .open(new_challenge_filename)
-            .expect("unable to create new challenge file in this directory");
+    // writes a fresh challenge and returns its path
+    fn challenge<E: Engine + Sync>(name: &str, parameters: &Phase1Parameters<E>) -> String {
+        let challenge = temp_path(&format!("{}_challenge", name));
+        new_challenge(UseCompression::No, &challenge, parameters);
+        challenge
+    }
 
-        // Recomputation strips the public key and uses hashing to link with the previous contribution after decompression
-        writer
-            .set_len(parameters.accumulator_size as u64)
-            .expect("must make output file large enough");
-
-        let mut writable_map = unsafe {
-            MmapOptions::new()
-                .map_mut(&writer)
-                .expect("unable to create a memory map for output")
-        };
+    // contributes to the challenge and returns the path of the compressed response
+    fn respond<E: Engine + Sync>(name: &str, challenge: &str, parameters: &Phase1Parameters<E>) -> String {
+        let response = temp_path(&format!("{}_response", name));
+        contribute(
+            UseCompression::No,
+            challenge,
+            UseCompression::Yes,
+            &response,
+            CheckForCorrectness::Full,
+            parameters,
+            thread_rng(),
+        );
+        response
+    }
 
-        {
-            (&mut writable_map[0..])
-                .write_all(response_hash.as_slice())
-                .expect("unable to write a default hash to mmap");
+    #[test]
+    fn verify_responses() {
+        verify_responses_curve::<Bls12_377>()
+    }
 
-            writable_map
-                .flush()
-                .expect("unable to write hash to new challenge file");
-        }
+    fn verify_responses_curve<E: Engine + Sync>() {
+        let parameters = Phase1Parameters::<E>::new_full(ProvingSystem::Groth16, 3, 4);
+        let challenge = challenge("verify_responses", &parameters);
+        let valid = respond("verify_responses_valid", &challenge, &parameters);
+        let invalid = respond("verify_responses_invalid", &challenge, &parameters);
+
+        // corrupt one of the powers of tau in G1, leaving the hash chain and the public key intact
+        let mut bytes = fs::read(&invalid).unwrap();
+        let position = parameters.hash_size + 2 * parameters.curve.g1_compressed_size + 1;
+        bytes[position] ^= 1;
+        fs::write(&invalid, &bytes).unwrap();
+
+        let results = super::verify_responses(
+            UseCompression::No,
+            &challenge,
+            UseCompression::Yes,
+            &[&valid, &invalid],
+            &parameters,
+        )
+        .unwrap();
+        assert_eq!(results.len(), 2);
+        let valid_hash = results[0].as_ref().unwrap();
+        assert_eq!(valid_hash, &calculate_hash(&fs::read(&valid).unwrap()));
+        assert!(matches!(results[1], Err(Phase1CliError::InvalidContribution(_))));
+    }
 
-        Phase1::decompress(
-            &response_readable_map,
-            &mut writable_map,
-            CheckForCorrectness::No,
+    #[test]
+    fn verify_response_streaming_matches_mmap() {
+        // windows which divide the number of powers, and ones which do not
+        verify_response_streaming_matches_mmap_curve::<Bls12_377>(4);
+        verify_response_streaming_matches_mmap_curve::<Bls12_377>(3);
+    }
+
+    fn verify_response_streaming_matches_mmap_curve<E: Engine + Sync>(batch_size: usize) {
+        let parameters = Phase1Parameters::<E>::new_full(ProvingSystem::Groth16, 3, batch_size);
+        let name = format!("streaming_{}", batch_size);
+        let challenge = challenge(&name, &parameters);
+        let response = respond(&name, &challenge, &parameters);
+
+        let mmap_hash = super::verify_responses(
+            UseCompression::No,
+            &challenge,
+            UseCompression::Yes,
+            &[&response],
+            &parameters,
+        )
+        .unwrap()
+        .remove(0)
+        .unwrap();
+        let streaming_hash = verify_response_streaming(
+            UseCompression::No,
+            &challenge,
+            UseCompression::Yes,
+            &response,
+            &parameters,
+        )
+        .unwrap();
+        assert_eq!(streaming_hash, mmap_hash);
+
+        // both reject the same corrupted power
+        let mut bytes = fs::read(&response).unwrap();
+        let position = parameters.hash_size + 2 * parameters.curve.g1_compressed_size + 1;
+        bytes[position] ^= 1;
+        fs::write(&response, &bytes).unwrap();
+        let results = super::verify_responses(
+            UseCompression::No,
+            &challenge,
+            UseCompression::Yes,
+            &[&response],
+            &parameters,
+        )
+        .unwrap();
+        assert!(results[0].is_err());
+        verify_response_streaming(
+            UseCompression::No,
+            &challenge,
+            UseCompression::Yes,
+            &response,
             &parameters,
         )
-        .expect("must decompress a response for a new challenge");
+        .unwrap_err();
+    }
 
-        writable_map.flush().expect("must flush the memory map");
+    #[test]
+    fn response_to_another_challenge() {
+        response_to_another_challenge_curve::<Bls12_377>()
+    }
 
-        let new_challenge_readable_map = writable_map.make_read_only().expect("must make a map readonly");
+    fn response_to_another_challenge_curve<E: Engine + Sync>() {
+        let parameters = Phase1Parameters::<E>::new_full(ProvingSystem::Groth16, 3, 4);
+        let challenge = challenge("another_challenge", &parameters);
+        // a challenge with the same powers but a different hash
+        let other = temp_path("another_challenge_other");
+        let mut bytes = fs::read(&challenge).unwrap();
+        bytes[0] ^= 1;
+        fs::write(&other, &bytes).unwrap();
+        let response = respond("another_challenge", &other, &parameters);
+
+        let expected = calculate_hash(&fs::read(&challenge).unwrap());
+        let found = calculate_hash(&bytes);
+        let is_mismatch = |err: &Phase1CliError| match err {
+            Phase1CliError::HashChainMismatch { expected: e, found: f } => e[..] == expected[..] && f[..] == found[..],
+            _ => false,
+        };
 
-        let recompressed_hash = calculate_hash(&new_challenge_readable_map);
+        let results = super::verify_responses(
+            UseCompression::No,
+            &challenge,
+            UseCompression::Yes,
+            &[&response],
+            &parameters,
+        )
+        .unwrap();
+        assert!(is_mismatch(results[0].as_ref().unwrap_err()));
+
+        let err = verify_response_streaming(
+            UseCompression::No,
+            &challenge,
+            UseCompression::Yes,
+            &response,
+            &parameters,
+        )
+        .unwrap_err();
+        assert!(is_mismatch(&err));
+    }
 
-        println!("Here's the BLAKE2b hash of the decompressed participant's response as new_challenge file:");
-        print_hash(&recompressed_hash);
-        println!("Done! new challenge file contains the new challenge file. The other files");
-        println!("were left alone.");
+    #[test]
+    fn new_challenge_is_complete() {
+        let path = temp_path("new_challenge_is_complete");
+        let length = 128;
+        let response_hash = calculate_hash(&[1u8; 32]);
+
+        // nothing was written yet
+        assert!(!super::new_challenge_is_complete(&path, length, &response_hash).unwrap());
+
+        // a complete file starts with the response hash
+        let mut complete = response_hash.to_vec();
+        complete.resize(length, 2);
+        fs::write(&path, &complete).unwrap();
+        assert!(super::new_challenge_is_complete(&path, length, &response_hash).unwrap());
+
+        // a truncated one was interrupted, even though it starts with the hash
+        fs::write(&path, &complete[..length - 1]).unwrap();
+        assert!(!super::new_challenge_is_complete(&path, length, &response_hash).unwrap());
+
+        // and so was one of the right size which does not have the hash yet
+        fs::write(&path, vec![0u8; length]).unwrap();
+        assert!(!super::new_challenge_is_complete(&path, length, &response_hash).unwrap());
+
+        fs::remove_file(&path).unwrap();
     }
 }
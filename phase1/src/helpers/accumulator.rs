@@ -8,10 +8,6 @@ use snarkvm_curves::{AffineCurve, PairingEngine};
 
 #[cfg(not(feature = "wasm"))]
 use crate::ContributionMode;
-#[cfg(not(feature = "wasm"))]
-use snarkvm_fields::{FieldParameters, PrimeField, Zero};
-#[cfg(not(feature = "wasm"))]
-use snarkvm_utilities::BitIteratorBE;
 
 #[allow(type_alias_bounds)]
 type AccumulatorElements<E: PairingEngine> = (
@@ -97,15 +93,7 @@ cfg_if! {
                 compression,
                 CheckForCorrectness::Full,
             )?;
-            // TODO(kobi): replace with batch subgroup check
-            let all_in_prime_order_subgroup = elements.iter().all(|p| {
-                p.mul_bits(BitIteratorBE::new(<<C::ScalarField as PrimeField>::Parameters as FieldParameters>::MODULUS))
-                    .is_zero()
-            });
-            if !all_in_prime_order_subgroup {
-                return Err(Error::IncorrectSubgroup);
-            }
-            Ok(())
+            check_subgroup(elements)
         }
 
         /// Reads a chunk of 2 elements from the buffer
@@ -3,7 +3,7 @@ use setup_utils::UseCompression;
 use snarkvm_curves::PairingEngine;
 use snarkvm_utilities::ConstantSerializedSize;
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
 use std::marker::PhantomData;
 
 #[derive(Clone, PartialEq, Eq, Debug, Copy, Serialize, Deserialize)]
@@ -18,8 +18,10 @@ pub enum ProvingSystem {
     Marlin,
 }
 
-/// The sizes of the group elements of a curve
-#[derive(Clone, PartialEq, Eq, Default, Debug)]
+/// The sizes of the group elements of a curve. When deserialized, the sizes
+/// must be the ones of `E`.
+#[derive(Clone, PartialEq, Eq, Default, Debug, Serialize)]
+#[serde(bound = "")]
 pub struct CurveParameters<E> {
     /// Size of a G1 Element
     pub g1_size: usize,
@@ -29,6 +31,7 @@ pub struct CurveParameters<E> {
     pub g1_compressed_size: usize,
     /// Size of a compressed G2 Element
     pub g2_compressed_size: usize,
+    #[serde(skip)]
     engine_type: PhantomData<E>,
 }
 
@@ -44,8 +47,47 @@ impl<E: PairingEngine> CurveParameters<E> {
     }
 }
 
-/// The parameters used for the trusted setup ceremony
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Deserializes the sizes and checks them against the ones of `E`
+impl<'de, E: PairingEngine> Deserialize<'de> for CurveParameters<E> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Sizes {
+            g1_size: usize,
+            g2_size: usize,
+            g1_compressed_size: usize,
+            g2_compressed_size: usize,
+        }
+
+        let sizes = Sizes::deserialize(deserializer)?;
+        let curve = CurveParameters::<E>::new();
+        let got = (
+            sizes.g1_size,
+            sizes.g2_size,
+            sizes.g1_compressed_size,
+            sizes.g2_compressed_size,
+        );
+        let expected = (
+            curve.g1_size,
+            curve.g2_size,
+            curve.g1_compressed_size,
+            curve.g2_compressed_size,
+        );
+        if got != expected {
+            return Err(de::Error::custom(format!(
+                "the curve sizes {:?} are not the ones of the curve, expected {:?}",
+                got, expected
+            )));
+        }
+        Ok(curve)
+    }
+}
+
+/// The parameters used for the trusted setup ceremony. They can be serialized
+/// so that a coordinator can publish the exact settings of the ceremony. Only
+/// the arguments of `Phase1Parameters::new` are deserialized, the sizes derived
+/// from them are computed again instead of being trusted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(bound = "")]
 pub struct Phase1Parameters<E> {
     /// The contribution mode
     pub contribution_mode: ContributionMode,
@@ -80,6 +122,63 @@ pub struct Phase1Parameters<E> {
     pub hash_size: usize,
 }
 
+impl<'de, E: PairingEngine> Deserialize<'de> for Phase1Parameters<E> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(bound = "")]
+        struct Inputs<E: PairingEngine> {
+            contribution_mode: ContributionMode,
+            chunk_index: usize,
+            chunk_size: usize,
+            curve: CurveParameters<E>,
+            proving_system: ProvingSystem,
+            total_size_in_log2: usize,
+            batch_size: usize,
+        }
+
+        let inputs = Inputs::<E>::deserialize(deserializer)?;
+        // `new` shifts by `total_size_in_log2 + 1`
+        if inputs.total_size_in_log2 >= std::mem::size_of::<usize>() * 8 - 1 {
+            return Err(de::Error::custom(format!(
+                "total_size_in_log2 of {} is too large",
+                inputs.total_size_in_log2
+            )));
+        }
+        if inputs.batch_size == 0 {
+            return Err(de::Error::custom("batch_size must not be 0"));
+        }
+        if inputs.contribution_mode == ContributionMode::Chunked {
+            let powers_length = 1 << inputs.total_size_in_log2;
+            let upper_bound = match inputs.proving_system {
+                ProvingSystem::Groth16 => (powers_length << 1) - 1,
+                ProvingSystem::Marlin => powers_length,
+            };
+            // the chunk must not start past the end of the powers
+            let start = inputs.chunk_index.checked_mul(inputs.chunk_size);
+            let end = start.and_then(|start| start.checked_add(inputs.chunk_size));
+            match (start, end) {
+                (Some(start), Some(_)) if start <= upper_bound => {}
+                _ => {
+                    return Err(de::Error::custom(format!(
+                        "chunk {} of size {} is out of range",
+                        inputs.chunk_index, inputs.chunk_size
+                    )));
+                }
+            }
+        }
+
+        Ok(Self::new(
+            inputs.contribution_mode,
+            inputs.chunk_index,
+            inputs.chunk_size,
+            inputs.curve,
+            inputs.proving_system,
+            inputs.total_size_in_log2,
+            inputs.batch_size,
+        ))
+    }
+}
+
 impl<E: PairingEngine> Phase1Parameters<E> {
     /// Constructs a new ceremony parameters object from the type of provided curve
     /// Panics if given batch_size = 0
@@ -322,4 +421,55 @@ mod tests {
         curve_parameters_test::<Bls12_377>(96, 192, 48, 96);
         curve_parameters_test::<BW6_761>(192, 192, 96, 96);
     }
+
+    fn serde_roundtrip_test<E: PairingEngine>(parameters: Phase1Parameters<E>) {
+        let serialized = serde_json::to_string(&parameters).unwrap();
+        let deserialized: Phase1Parameters<E> = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, parameters);
+    }
+
+    #[test]
+    fn test_parameters_serde_rebuilds_derived_sizes() {
+        let parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 10, 16);
+        let mut value = serde_json::to_value(&parameters).unwrap();
+        value["g1_chunk_size"] = 1usize.into();
+        value["accumulator_size"] = 0usize.into();
+        let deserialized: Phase1Parameters<Bls12_377> = serde_json::from_value(value).unwrap();
+        assert_eq!(deserialized, parameters);
+    }
+
+    #[test]
+    fn test_parameters_serde_rejects_inconsistent_inputs() {
+        let parameters =
+            Phase1Parameters::<Bls12_377>::new_chunk(ContributionMode::Chunked, 1, 64, ProvingSystem::Groth16, 10, 16);
+        let value = serde_json::to_value(&parameters).unwrap();
+
+        // the sizes of another curve
+        let mut wrong_curve = value.clone();
+        wrong_curve["curve"]["g1_size"] = 192usize.into();
+        assert!(serde_json::from_value::<Phase1Parameters<Bls12_377>>(wrong_curve).is_err());
+
+        // a chunk past the end of the powers
+        let mut out_of_range = value.clone();
+        out_of_range["chunk_index"] = 1000usize.into();
+        assert!(serde_json::from_value::<Phase1Parameters<Bls12_377>>(out_of_range).is_err());
+
+        // a size which does not fit in a usize
+        let mut too_large = value;
+        too_large["total_size_in_log2"] = 64usize.into();
+        assert!(serde_json::from_value::<Phase1Parameters<Bls12_377>>(too_large).is_err());
+    }
+
+    #[test]
+    fn test_parameters_serde() {
+        serde_roundtrip_test(Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 10, 16));
+        serde_roundtrip_test(Phase1Parameters::<BW6_761>::new_chunk(
+            ContributionMode::Chunked,
+            1,
+            64,
+            ProvingSystem::Marlin,
+            10,
+            16,
+        ));
+    }
 }
@@ -0,0 +1,111 @@
+use phase1::helpers::testing::random_point_vec;
+use phase2::{
+    helpers::testing::TestCircuit,
+    parameters::{circuit_to_qap, MPCParameters},
+};
+use setup_utils::Groth16Params;
+
+use snarkvm_algorithms::snark::groth16::KeypairAssembly;
+use snarkvm_curves::{bls12_377::Bls12_377, PairingEngine};
+use snarkvm_fields::Field;
+use snarkvm_r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisError};
+use snarkvm_utilities::{CanonicalDeserialize, CanonicalSerialize};
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use rand::thread_rng;
+
+type Fr = <Bls12_377 as PairingEngine>::Fr;
+
+// circuit made of `n` chained squarings, to benchmark large QAPs
+#[derive(Clone)]
+struct SquaringCircuit(usize);
+impl ConstraintSynthesizer<Fr> for SquaringCircuit {
+    fn generate_constraints<CS: ConstraintSystem<Fr>>(&self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let mut x_val = Fr::one().double();
+        let mut x = cs.alloc(|| "x", || Ok(x_val))?;
+        for _ in 0..self.0 {
+            let square_val = x_val.square();
+            let square = cs.alloc(|| "square", || Ok(square_val))?;
+            cs.enforce(|| "x * x = square", |lc| lc + x, |lc| lc + x, |lc| lc + square);
+            x = square;
+            x_val = square_val;
+        }
+        Ok(())
+    }
+}
+
+// the serialization round-trip which `circuit_to_qap` skips when both engines are the same,
+// if the `unsafe-fast-qap` feature is enabled
+fn round_trip(assembly: &KeypairAssembly<Bls12_377>) -> KeypairAssembly<Bls12_377> {
+    let mut serialized = Vec::new();
+    assembly.serialize(&mut serialized).unwrap();
+    KeypairAssembly::deserialize(&mut &serialized[..]).unwrap()
+}
+
+// Benchmark comparing the QAP conversion with the serialization round-trip it avoids.
+// Run it with and without the `unsafe-fast-qap` feature to measure the difference.
+fn benchmark_circuit_to_qap(c: &mut Criterion) {
+    let mut group = c.benchmark_group("CircuitToQap");
+    group.sample_size(10);
+
+    group.bench_function("test_circuit", |b| {
+        b.iter(|| circuit_to_qap::<Bls12_377, Bls12_377, _>(TestCircuit::<Bls12_377>(None)).unwrap())
+    });
+    let assembly = circuit_to_qap::<Bls12_377, Bls12_377, _>(TestCircuit::<Bls12_377>(None)).unwrap();
+    group.bench_function("test_circuit_round_trip", |b| b.iter(|| round_trip(&assembly)));
+
+    for size in (10..17).step_by(2).map(|i| 2usize.pow(i)) {
+        group.throughput(Throughput::Elements(size as u64));
+        let circuit = SquaringCircuit(size);
+        group.bench_with_input("squarings", &size, |b, _size| {
+            b.iter(|| circuit_to_qap::<Bls12_377, Bls12_377, _>(circuit.clone()).unwrap())
+        });
+        let assembly = circuit_to_qap::<Bls12_377, Bls12_377, _>(circuit.clone()).unwrap();
+        group.bench_with_input("squarings_round_trip", &size, |b, _size| {
+            b.iter(|| round_trip(&assembly))
+        });
+    }
+    group.finish();
+}
+
+// Benchmark evaluating the QAP of a 2^16 circuit over random Lagrange coefficients
+fn benchmark_new(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NewParameters");
+    group.sample_size(10);
+
+    let rng = &mut thread_rng();
+    let size = 2usize.pow(16);
+    // the circuit has `size - 2` squarings, for a total of `size` variables
+    let circuit = SquaringCircuit(size - 2);
+    let coeffs_g1 = random_point_vec(size, rng);
+    let coeffs_g2 = random_point_vec(size, rng);
+    let alpha_coeffs_g1 = random_point_vec(size, rng);
+    let beta_coeffs_g1 = random_point_vec(size, rng);
+    let h_g1 = random_point_vec(size - 1, rng);
+
+    group.throughput(Throughput::Elements(size as u64));
+    group.bench_function("squarings", |b| {
+        b.iter_batched(
+            || {
+                let assembly = circuit_to_qap::<Bls12_377, Bls12_377, _>(circuit.clone()).unwrap();
+                let params = Groth16Params::<Bls12_377> {
+                    alpha_g1: alpha_coeffs_g1[0],
+                    beta_g1: beta_coeffs_g1[0],
+                    beta_g2: coeffs_g2[0],
+                    coeffs_g1: coeffs_g1.clone(),
+                    coeffs_g2: coeffs_g2.clone(),
+                    alpha_coeffs_g1: alpha_coeffs_g1.clone(),
+                    beta_coeffs_g1: beta_coeffs_g1.clone(),
+                    h_g1: h_g1.clone(),
+                };
+                (assembly, params)
+            },
+            |(assembly, params)| MPCParameters::new(assembly, params).unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_circuit_to_qap, benchmark_new);
+criterion_main!(benches);
@@ -0,0 +1,80 @@
+use phase2::{
+    keypair::{hash_cs_pubkeys, Keypair, PublicKey},
+    parameters::{verify_transcript, verify_transcript_with_audit},
+};
+use setup_utils::{check_same_ratio, HASH_LEN};
+
+use snarkvm_algorithms::hash_to_curve::hash_to_curve;
+use snarkvm_curves::{bls12_377::Bls12_377, AffineCurve, PairingEngine};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rand::thread_rng;
+
+type G1Affine = <Bls12_377 as PairingEngine>::G1Affine;
+type G2Affine = <Bls12_377 as PairingEngine>::G2Affine;
+
+// a valid chain of `n` contributions to the same circuit
+fn chain(n: usize) -> ([u8; HASH_LEN], Vec<PublicKey<Bls12_377>>) {
+    let rng = &mut thread_rng();
+    let cs_hash = [1; HASH_LEN];
+    let mut contributions = vec![];
+    let mut delta_g1 = G1Affine::prime_subgroup_generator();
+    for _ in 0..n {
+        let pubkey = Keypair::<Bls12_377>::new(delta_g1, cs_hash, &contributions, rng).public_key;
+        delta_g1 = pubkey.delta_after;
+        contributions.push(pubkey);
+    }
+    (cs_hash, contributions)
+}
+
+// the transcript check with `hash_to_curve` inside the delta chain loop
+fn verify_transcript_serial(cs_hash: [u8; HASH_LEN], contributions: &[PublicKey<Bls12_377>]) -> Vec<[u8; HASH_LEN]> {
+    let mut old_delta = G1Affine::prime_subgroup_generator();
+    let mut result = vec![];
+    for (i, pubkey) in contributions.iter().enumerate() {
+        let hash = hash_cs_pubkeys(
+            cs_hash,
+            &contributions[0..i],
+            pubkey.s,
+            pubkey.s_delta,
+            pubkey.identity(),
+        );
+        assert_eq!(&pubkey.transcript[..], &hash[..]);
+        let r = hash_to_curve::<G2Affine>(&hex::encode(hash.as_ref())).0;
+        check_same_ratio::<Bls12_377>(&(pubkey.s, pubkey.s_delta), &(r, pubkey.r_delta), "").unwrap();
+        check_same_ratio::<Bls12_377>(&(old_delta, pubkey.delta_after), &(r, pubkey.r_delta), "").unwrap();
+        old_delta = pubkey.delta_after;
+        result.push(pubkey.hash());
+    }
+    result
+}
+
+// Benchmark verifying a 200 contribution transcript, with the pairing checks
+// batched, checked one by one with the `r` points derived ahead of the delta
+// chain, or checked one by one with the `r` points derived inside of it
+fn benchmark_verify_transcript(c: &mut Criterion) {
+    let mut group = c.benchmark_group("VerifyTranscript");
+    group.sample_size(10);
+
+    let size = 200;
+    let (cs_hash, contributions) = chain(size);
+    assert_eq!(
+        verify_transcript(cs_hash, &contributions).unwrap(),
+        verify_transcript_serial(cs_hash, &contributions)
+    );
+
+    group.throughput(Throughput::Elements(size as u64));
+    group.bench_function("batched", |b| {
+        b.iter(|| verify_transcript(cs_hash, &contributions).unwrap())
+    });
+    group.bench_function("hoisted", |b| {
+        b.iter(|| verify_transcript_with_audit(cs_hash, &contributions).unwrap())
+    });
+    group.bench_function("serial", |b| {
+        b.iter(|| verify_transcript_serial(cs_hash, &contributions))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_verify_transcript);
+criterion_main!(benches);
@@ -4,10 +4,11 @@
 //! utilities for operating directly on raw items which implement `Read`, `Write` and `Seek`
 //! such that contributing and verifying the MPC can be done in chunks which fit in memory.
 use crate::{
-    keypair::{Keypair, PublicKey},
+    helpers::ensure_unchanged_hash,
+    keypair::{Keypair, PublicKey, SecretScalar},
     parameters::*,
 };
-use setup_utils::{batch_mul, check_same_ratio, merge_pairs, InvariantKind, Phase2Error, Result};
+use setup_utils::{batch_mul, check_same_ratio, merge_pairs, Error, InvariantKind, Phase2Error, Result, HASH_LEN};
 use snarkvm_algorithms::snark::groth16::VerifyingKey;
 use snarkvm_curves::{AffineCurve, PairingEngine};
 use snarkvm_fields::Field;
@@ -16,16 +17,25 @@ use snarkvm_utilities::{CanonicalDeserialize, CanonicalSerialize, ConstantSerial
 use byteorder::{BigEndian, WriteBytesExt};
 use rand::{CryptoRng, Rng};
 use std::{
-    io::{Read, Seek, SeekFrom, Write},
-    ops::{Mul, Neg},
+    cmp::min,
+    io::{Cursor, Read, Seek, SeekFrom, Write},
+    ops::{Mul, Neg, Range},
 };
 use tracing::{debug, info, info_span, trace};
 
+#[cfg(not(feature = "wasm"))]
+use memmap::MmapOptions;
+#[cfg(not(feature = "wasm"))]
+use std::{
+    fs::{self, File, OpenOptions},
+    path::{Path, PathBuf},
+};
+
 /// Given two serialized contributions to the ceremony, this will check that `after`
 /// has been correctly calculated from `before`. Large vectors will be read in
 /// `batch_size` batches
 #[allow(clippy::cognitive_complexity)]
-pub fn verify<E: PairingEngine>(before: &mut [u8], after: &mut [u8], batch_size: usize) -> Result<Vec<[u8; 64]>> {
+pub fn verify<E: PairingEngine>(before: &mut [u8], after: &mut [u8], batch_size: usize) -> Result<Vec<[u8; HASH_LEN]>> {
     let span = info_span!("phase2-verify");
     let _enter = span.enter();
     info!("starting...");
@@ -33,6 +43,9 @@ pub fn verify<E: PairingEngine>(before: &mut [u8], after: &mut [u8], batch_size:
     let before = &mut std::io::Cursor::new(before);
     let after = &mut std::io::Cursor::new(after);
 
+    read_header::<E, _>(before)?;
+    read_header::<E, _>(after)?;
+
     let vk_before = VerifyingKey::<E>::deserialize(before)?;
     let beta_g1_before = E::G1Affine::deserialize(before)?;
     // we don't need the previous delta_g1 so we can skip it
@@ -48,6 +61,12 @@ pub fn verify<E: PairingEngine>(before: &mut [u8], after: &mut [u8], batch_size:
     ensure_unchanged(vk_before.alpha_g1, vk_after.alpha_g1, InvariantKind::AlphaG1)?;
     ensure_unchanged(beta_g1_before, beta_g1_after, InvariantKind::BetaG1)?;
     ensure_unchanged(vk_before.beta_g2, vk_after.beta_g2, InvariantKind::BetaG2)?;
+    // Beta in G1 and G2 should correspond to the same scalar
+    check_same_ratio::<E>(
+        &(E::G1Affine::prime_subgroup_generator(), beta_g1_after),
+        &(E::G2Affine::prime_subgroup_generator(), vk_after.beta_g2),
+        "Inconsistent Beta",
+    )?;
     ensure_unchanged(vk_before.gamma_g2, vk_after.gamma_g2, InvariantKind::GammaG2)?;
     ensure_unchanged_vec(
         &vk_before.gamma_abc_g1,
@@ -155,14 +174,23 @@ pub fn verify<E: PairingEngine>(before: &mut [u8], after: &mut [u8], batch_size:
     after.seek(SeekFrom::Start(pos as u64))?;
 
     // cs_hash should be the same
-    let mut cs_hash_before = [0u8; 64];
+    let mut cs_hash_before = [0u8; HASH_LEN];
     before.read_exact(&mut cs_hash_before)?;
-    let mut cs_hash_after = [0u8; 64];
+    let mut cs_hash_after = [0u8; HASH_LEN];
     after.read_exact(&mut cs_hash_after)?;
-    ensure_unchanged(&cs_hash_before[..], &cs_hash_after[..], InvariantKind::CsHash)?;
+    ensure_unchanged_hash(&cs_hash_before, &cs_hash_after, InvariantKind::CsHash)?;
 
     debug!("cs hash was unchanged");
 
+    // and so should the phase 1 digest
+    let mut phase1_digest_before = [0u8; HASH_LEN];
+    before.read_exact(&mut phase1_digest_before)?;
+    let mut phase1_digest_after = [0u8; HASH_LEN];
+    after.read_exact(&mut phase1_digest_after)?;
+    ensure_unchanged_hash(&phase1_digest_before, &phase1_digest_after, InvariantKind::Phase1Digest)?;
+
+    debug!("phase 1 digest was unchanged");
+
     // None of the previous transformations should change
     let contributions_before = PublicKey::<E>::read_batch(before)?;
     let contributions_after = PublicKey::<E>::read_batch(after)?;
@@ -208,14 +236,15 @@ pub fn contribute<E: PairingEngine, R: Rng + CryptoRng>(
     buffer: &mut [u8],
     rng: &mut R,
     batch_size: usize,
-) -> Result<[u8; 64]> {
+) -> Result<[u8; HASH_LEN]> {
     let span = info_span!("phase2-contribute");
     let _enter = span.enter();
 
     info!("starting...");
 
     let buffer = &mut std::io::Cursor::new(buffer);
-    // The VK is small so we read it directly from the start
+    // The VK is small so we read it directly after the header
+    read_header::<E, _>(buffer)?;
     let mut vk = VerifyingKey::<E>::deserialize(buffer)?;
     // leave beta_g1 unchanged
     buffer.seek(SeekFrom::Current(E::G1Affine::SERIALIZED_SIZE as i64))?;
@@ -226,13 +255,19 @@ pub fn contribute<E: PairingEngine, R: Rng + CryptoRng>(
     skip_vec::<E::G1Affine, _>(buffer)?; // Alpha G1
     skip_vec::<E::G1Affine, _>(buffer)?; // Beta G1
     skip_vec::<E::G2Affine, _>(buffer)?; // Beta G2
-    skip_vec::<E::G1Affine, _>(buffer)?; // H
-    skip_vec::<E::G1Affine, _>(buffer)?; // L
+    let h_query_len = skip_vec::<E::G1Affine, _>(buffer)?; // H
+    let l_query_len = skip_vec::<E::G1Affine, _>(buffer)?; // L
+    // Bail before anything gets written if there is nothing to contribute to
+    ensure_contributable(h_query_len, l_query_len)?;
 
     // Read the transcript hash and the contributions
-    let mut cs_hash = [0u8; 64];
+    let mut cs_hash = [0u8; HASH_LEN];
     buffer.read_exact(&mut cs_hash)?;
+    // skip the phase 1 digest
+    buffer.seek(SeekFrom::Current(HASH_LEN as i64))?;
+    let contributions_start = buffer.position();
     let contributions = PublicKey::<E>::read_batch(buffer)?;
+    let contributions_end = buffer.position();
 
     // Create the keypair
     let Keypair {
@@ -240,16 +275,16 @@ pub fn contribute<E: PairingEngine, R: Rng + CryptoRng>(
         private_key,
     } = Keypair::new(delta_g1, cs_hash, &contributions, rng);
     let hash = public_key.hash();
-    // THIS MUST BE DESTROYED
-    let delta = private_key.delta;
-    let delta_inv = private_key.delta.inverse().expect("nonzero");
+    let delta_inv = private_key.delta_inv();
 
     // update the values
-    delta_g1 = delta_g1.mul(delta);
-    vk.delta_g2 = vk.delta_g2.mul(delta);
+    delta_g1 = delta_g1.mul(private_key.delta);
+    vk.delta_g2 = vk.delta_g2.mul(private_key.delta);
+    // Ensure the private key is no longer used
+    drop(private_key);
 
-    // go back to the start of the buffer to write the updated vk and delta_g1
-    buffer.seek(SeekFrom::Start(0))?;
+    // go back to the end of the header to write the updated vk and delta_g1
+    buffer.seek(SeekFrom::Start(PARAMS_HEADER_SIZE as u64))?;
     // write the vk
     vk.serialize(buffer)?;
     // leave beta_g1 unchanged
@@ -305,21 +340,16 @@ pub fn contribute<E: PairingEngine, R: Rng + CryptoRng>(
 
         Ok(())
     })??;
+    drop(delta_inv);
 
     debug!("appending contribution...");
 
-    // we processed the 2 elements via the raw buffer, so we have to modify the cursor accordingly
-    let pos = position + (l_query_len + h_query_len) * E::G1Affine::SERIALIZED_SIZE + u64::SERIALIZED_SIZE;
-    buffer.seek(SeekFrom::Start(pos as u64))?;
-
-    // leave the cs_hash unchanged (64 bytes size)
-    buffer.seek(SeekFrom::Current(64))?;
-
-    // update the pubkeys length
+    // update the pubkeys length, leaving the cs_hash and the phase 1 digest unchanged
+    buffer.seek(SeekFrom::Start(contributions_start))?;
     buffer.write_u32::<BigEndian>((contributions.len() + 1) as u32)?;
 
-    // advance to where the next pubkey would be in the buffer and append it
-    buffer.seek(SeekFrom::Current((PublicKey::<E>::size() * contributions.len()) as i64))?;
+    // append the pubkey after the last one
+    buffer.seek(SeekFrom::Start(contributions_end))?;
     public_key.write(buffer)?;
 
     info!("done.");
@@ -327,12 +357,439 @@ pub fn contribute<E: PairingEngine, R: Rng + CryptoRng>(
     Ok(hash)
 }
 
-/// Skips the vector ahead of the cursor.
-fn skip_vec<C: AffineCurve, B: Read + Seek>(buffer: &mut B) -> Result<()> {
+/// Multiplies a chunk of the serialized H or L query (without its length prefix)
+/// by a `delta_inv` which is shared by all the chunks of the contribution, in
+/// `batch_size` batches. This lets a coordinator generate the contribution's
+/// keypair once and have workers process the chunks independently; the delta
+/// of the parameters and the public key must still be updated separately.
+///
+/// **`delta_inv` is toxic waste.** It must only be transmitted to the workers
+/// over a secure channel, and every copy of it must be destroyed as soon as the
+/// chunks have been processed. Taking it as a `SecretScalar` ensures the workers'
+/// copies are zeroized when they are dropped.
+pub fn contribute_chunk<E: PairingEngine>(
+    chunk: &mut [u8],
+    delta_inv: &SecretScalar<E::Fr>,
+    batch_size: usize,
+) -> Result<()> {
+    let span = info_span!("phase2-contribute-chunk");
+    let _enter = span.enter();
+
+    let size = E::G1Affine::SERIALIZED_SIZE;
+    if chunk.len() % size != 0 {
+        return Err(Error::InvalidLength {
+            expected: chunk.len() - chunk.len() % size,
+            got: chunk.len(),
+        });
+    }
+    let len = chunk.len() / size;
+    chunked_mul_queries::<E::G1Affine>(chunk, len, delta_inv, batch_size)
+}
+
+/// Checks that the elements `range` of the H or L query were updated with the
+/// contribution's delta, so that a pool of workers can each verify a part of the
+/// query. `before_chunk` and `after_chunk` are the serialized elements of that
+/// range (without the query's length prefix), as produced by `contribute_chunk`.
+/// The coordinator must check that the verified ranges cover the whole query
+/// with `ensure_ranges_cover`, and run the rest of the checks of `verify` itself.
+///
+/// **`delta_g2_before` and `delta_g2_after` must be read from the authenticated
+/// verifying keys of the parameters**, i.e. from parameters whose remaining
+/// checks passed, since a worker given forged deltas would accept forged chunks.
+pub fn verify_chunk_range<E: PairingEngine>(
+    before_chunk: &[u8],
+    after_chunk: &[u8],
+    delta_g2_before: E::G2Affine,
+    delta_g2_after: E::G2Affine,
+    range: Range<usize>,
+) -> Result<()> {
+    let span = info_span!("phase2-verify-chunk-range", start = range.start, end = range.end);
+    let _enter = span.enter();
+
+    let expected = range.len() * E::G1Affine::SERIALIZED_SIZE;
+    for chunk in &[before_chunk, after_chunk] {
+        if chunk.len() != expected {
+            return Err(Error::InvalidLength {
+                expected,
+                got: chunk.len(),
+            });
+        }
+    }
+
+    let read_chunk = |mut chunk: &[u8]| {
+        (0..range.len())
+            .map(|_| E::G1Affine::deserialize(&mut chunk))
+            .collect::<std::result::Result<Vec<_>, _>>()
+    };
+    let els_before = read_chunk(before_chunk)?;
+    let els_after = read_chunk(after_chunk)?;
+    ensure_dense(&els_after)?;
+    let pairs = merge_pairs(&els_before, &els_after);
+    check_same_ratio::<E>(
+        &pairs,
+        &(delta_g2_after, delta_g2_before),
+        "Query range ratio check failed",
+    )?;
+
+    debug!("done.");
+
+    Ok(())
+}
+
+/// Checks that the `ranges` verified with `verify_chunk_range` cover all the
+/// `len` elements of a query. The ranges may be in any order and may overlap.
+pub fn ensure_ranges_cover(ranges: &[Range<usize>], len: usize) -> Result<()> {
+    let mut ranges = ranges.to_vec();
+    ranges.sort_by_key(|range| range.start);
+
+    let mut covered = 0;
+    for range in ranges {
+        if range.start > covered {
+            return Err(Phase2Error::UncoveredRange {
+                start: covered,
+                end: range.start,
+            }
+            .into());
+        }
+        covered = covered.max(range.end);
+    }
+    if covered < len {
+        return Err(Phase2Error::UncoveredRange {
+            start: covered,
+            end: len,
+        }
+        .into());
+    }
+    if covered > len {
+        return Err(Error::InvalidLength {
+            expected: len,
+            got: covered,
+        });
+    }
+
+    Ok(())
+}
+
+/// The state needed to resume a contribution started with `contribute_resumable`
+/// after the process was interrupted.
+///
+/// **This holds the contribution's toxic waste (`delta_inv`).** It must only ever be
+/// persisted to storage controlled by the contributor, and must be destroyed as soon
+/// as the contribution has completed. For the same reason it cannot be cloned, and
+/// `delta_inv` is zeroized when it is dropped.
+pub struct ContributionCheckpoint<E: PairingEngine> {
+    /// The inverse of the contribution's secret delta
+    pub delta_inv: SecretScalar<E::Fr>,
+    /// The VK's delta in G2 after the contribution
+    pub delta_g2_after: E::G2Affine,
+    /// The public key which will be appended to the contributions
+    pub public_key: PublicKey<E>,
+    /// The number of contributions before this one
+    pub num_contributions: usize,
+    /// The number of elements of the H query which have been multiplied
+    pub h_done: usize,
+    /// The number of elements of the L query which have been multiplied
+    pub l_done: usize,
+    /// The offset in the buffer at which `pending` must be written
+    pub pending_offset: usize,
+    /// The last multiplied batch, which might not have reached the buffer
+    /// when the process was interrupted. It is written again on resumption.
+    pub pending: Vec<u8>,
+}
+
+impl<E: PairingEngine> ContributionCheckpoint<E> {
+    /// Serializes the checkpoint, including `delta_inv`. **The output is as secret as
+    /// the checkpoint itself**: a checkpoint file must be kept on the contributor's own
+    /// storage and securely deleted once the contribution has completed.
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.delta_inv.serialize(writer)?;
+        self.delta_g2_after.serialize(writer)?;
+        self.public_key.write(writer)?;
+        for &n in &[self.num_contributions, self.h_done, self.l_done, self.pending_offset] {
+            (n as u64).serialize(writer)?;
+        }
+        self.pending.serialize(writer)?;
+        Ok(())
+    }
+
+    pub fn read<R: Read>(reader: &mut R) -> Result<ContributionCheckpoint<E>> {
+        Ok(ContributionCheckpoint {
+            delta_inv: SecretScalar::new(E::Fr::deserialize(reader)?),
+            delta_g2_after: E::G2Affine::deserialize(reader)?,
+            public_key: PublicKey::read(reader)?,
+            num_contributions: u64::deserialize(reader)? as usize,
+            h_done: u64::deserialize(reader)? as usize,
+            l_done: u64::deserialize(reader)? as usize,
+            pending_offset: u64::deserialize(reader)? as usize,
+            pending: Vec::<u8>::deserialize(reader)?,
+        })
+    }
+}
+
+/// Same as `contribute`, but the H and L queries are processed serially in `batch_size`
+/// batches, and `on_checkpoint` is called before each batch is written back to the buffer.
+/// If the process gets interrupted, the contribution can be completed by passing the last
+/// persisted checkpoint to `resume_contribution`.
+///
+/// `on_checkpoint` is also called once before the buffer is modified, so that the keypair
+/// survives an interruption. Returning an error from it aborts the contribution.
+pub fn contribute_resumable<E, R, F>(
+    buffer: &mut [u8],
+    rng: &mut R,
+    batch_size: usize,
+    mut on_checkpoint: F,
+) -> Result<[u8; HASH_LEN]>
+where
+    E: PairingEngine,
+    R: Rng + CryptoRng,
+    F: FnMut(&ContributionCheckpoint<E>) -> Result<()>,
+{
+    let span = info_span!("phase2-contribute-resumable");
+    let _enter = span.enter();
+
+    let mut cursor = Cursor::new(&mut *buffer);
+    read_header::<E, _>(&mut cursor)?;
+    let vk = VerifyingKey::<E>::deserialize(&mut cursor)?;
+    // skip beta_g1
+    cursor.seek(SeekFrom::Current(E::G1Affine::SERIALIZED_SIZE as i64))?;
+    let delta_g1 = E::G1Affine::deserialize(&mut cursor)?;
+
+    skip_vec::<E::G1Affine, _>(&mut cursor)?; // Alpha G1
+    skip_vec::<E::G1Affine, _>(&mut cursor)?; // Beta G1
+    skip_vec::<E::G2Affine, _>(&mut cursor)?; // Beta G2
+    let h_query_len = skip_vec::<E::G1Affine, _>(&mut cursor)?; // H
+    let l_query_len = skip_vec::<E::G1Affine, _>(&mut cursor)?; // L
+    ensure_contributable(h_query_len, l_query_len)?;
+
+    let mut cs_hash = [0u8; HASH_LEN];
+    cursor.read_exact(&mut cs_hash)?;
+    // skip the phase 1 digest
+    cursor.seek(SeekFrom::Current(HASH_LEN as i64))?;
+    let contributions = PublicKey::<E>::read_batch(&mut cursor)?;
+
+    let Keypair {
+        public_key,
+        private_key,
+    } = Keypair::new(delta_g1, cs_hash, &contributions, rng);
+    let checkpoint = ContributionCheckpoint {
+        delta_inv: private_key.delta_inv(),
+        delta_g2_after: vk.delta_g2.mul(private_key.delta),
+        public_key,
+        num_contributions: contributions.len(),
+        h_done: 0,
+        l_done: 0,
+        pending_offset: 0,
+        pending: vec![],
+    };
+    // Ensure the private key is no longer used
+    drop(private_key);
+
+    // persist the keypair before anything gets written
+    on_checkpoint(&checkpoint)?;
+    debug!("created checkpoint");
+
+    resume_contribution(buffer, checkpoint, batch_size, on_checkpoint)
+}
+
+/// Completes a contribution which was started with `contribute_resumable` on the same
+/// buffer, starting from the last checkpoint which was persisted before the interruption.
+pub fn resume_contribution<E, F>(
+    buffer: &mut [u8],
+    mut checkpoint: ContributionCheckpoint<E>,
+    batch_size: usize,
+    mut on_checkpoint: F,
+) -> Result<[u8; HASH_LEN]>
+where
+    E: PairingEngine,
+    F: FnMut(&ContributionCheckpoint<E>) -> Result<()>,
+{
+    let span = info_span!("phase2-resume-contribution");
+    let _enter = span.enter();
+    info!("resuming from H: {}, L: {}", checkpoint.h_done, checkpoint.l_done);
+
+    // Replay the last batch in case it had not been written when we were interrupted
+    if !checkpoint.pending.is_empty() {
+        let end = checkpoint.pending_offset + checkpoint.pending.len();
+        let got = buffer.len();
+        buffer
+            .get_mut(checkpoint.pending_offset..end)
+            .ok_or(Error::InvalidLength { expected: end, got })?
+            .copy_from_slice(&checkpoint.pending);
+    }
+
+    let buffer = &mut Cursor::new(buffer);
+    read_header::<E, _>(buffer)?;
+    let mut vk = VerifyingKey::<E>::deserialize(buffer)?;
+    // skip beta_g1 and delta_g1
+    buffer.seek(SeekFrom::Current(2 * E::G1Affine::SERIALIZED_SIZE as i64))?;
+    skip_vec::<E::G1Affine, _>(buffer)?; // Alpha G1
+    skip_vec::<E::G1Affine, _>(buffer)?; // Beta G1
+    skip_vec::<E::G2Affine, _>(buffer)?; // Beta G2
+    let h_query_len = u64::deserialize(buffer)? as usize;
+    let h_query_start = buffer.position() as usize;
+    buffer.seek(SeekFrom::Current((h_query_len * E::G1Affine::SERIALIZED_SIZE) as i64))?;
+    let l_query_len = u64::deserialize(buffer)? as usize;
+    let l_query_start = buffer.position() as usize;
+    // skip the L query, the cs_hash and the phase 1 digest
+    let contributions_start = l_query_start + l_query_len * E::G1Affine::SERIALIZED_SIZE + 2 * HASH_LEN;
+
+    for &(start, len, is_l) in &[(h_query_start, h_query_len, false), (l_query_start, l_query_len, true)] {
+        loop {
+            let done = if is_l { checkpoint.l_done } else { checkpoint.h_done };
+            if done >= len {
+                break;
+            }
+            let num_els = min(batch_size, len - done);
+            let offset = start + done * E::G1Affine::SERIALIZED_SIZE;
+
+            buffer.seek(SeekFrom::Start(offset as u64))?;
+            let mut query = (0..num_els)
+                .map(|_| E::G1Affine::deserialize(buffer))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            batch_mul(&mut query, &*checkpoint.delta_inv)?;
+
+            let mut pending = Vec::with_capacity(num_els * E::G1Affine::SERIALIZED_SIZE);
+            query
+                .iter()
+                .map(|el| el.serialize(&mut pending))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            if is_l {
+                checkpoint.l_done += num_els;
+            } else {
+                checkpoint.h_done += num_els;
+            }
+            checkpoint.pending_offset = offset;
+            checkpoint.pending = pending;
+            on_checkpoint(&checkpoint)?;
+
+            buffer.seek(SeekFrom::Start(offset as u64))?;
+            buffer.write_all(&checkpoint.pending)?;
+            trace!("ok");
+        }
+    }
+
+    debug!("appending contribution...");
+
+    // All of the following writes are absolute, so they can safely be repeated
+    // if we get interrupted again
+    vk.delta_g2 = checkpoint.delta_g2_after;
+    buffer.seek(SeekFrom::Start(PARAMS_HEADER_SIZE as u64))?;
+    vk.serialize(buffer)?;
+    // leave beta_g1 unchanged
+    buffer.seek(SeekFrom::Current(E::G1Affine::SERIALIZED_SIZE as i64))?;
+    checkpoint.public_key.delta_after.serialize(buffer)?;
+
+    buffer.seek(SeekFrom::Start(contributions_start as u64))?;
+    buffer.write_u32::<BigEndian>((checkpoint.num_contributions + 1) as u32)?;
+    // The previous keys may have identities, so they are read to find where the new
+    // one goes. Only `num_contributions` of them are read, since the number of keys
+    // may already have been updated before an interruption.
+    for _ in 0..checkpoint.num_contributions {
+        PublicKey::<E>::read(buffer)?;
+    }
+    checkpoint.public_key.write(buffer)?;
+
+    info!("done.");
+
+    Ok(checkpoint.public_key.hash())
+}
+
+/// Writes to `out_path` the parameters of `contributed_path`, after checking with `verify`
+/// that they are a valid contribution to the parameters of `base_path`. Instead of being
+/// deserialized and written back, the output is a copy of `base_path` in which only the
+/// regions a contribution changes are overwritten: delta in G1 and G2, the H and L queries
+/// and the number of contributions, followed by the new public keys. `out_path` may be
+/// `base_path`, in which case the canonical file is updated. The output is first written
+/// to a temporary file next to `out_path` and then renamed over it, so `out_path` is
+/// either left untouched or fully written. The output is the same as reading
+/// `contributed_path`, verifying it and writing it back.
+///
+/// Returns the hashes of the contributions, like `verify`.
+#[cfg(not(feature = "wasm"))]
+pub fn append_contribution_to_file<E: PairingEngine>(
+    base_path: &Path,
+    contributed_path: &Path,
+    out_path: &Path,
+    batch_size: usize,
+) -> Result<Vec<[u8; HASH_LEN]>> {
+    let span = info_span!("phase2-append-contribution");
+    let _enter = span.enter();
+
+    // `verify` needs mutable buffers, so map both files copy-on-write: nothing is written back
+    let mut base = unsafe { MmapOptions::new().map_copy(&File::open(base_path)?)? };
+    let mut contributed = unsafe { MmapOptions::new().map_copy(&File::open(contributed_path)?)? };
+    let hashes = verify::<E>(&mut base, &mut contributed, batch_size)?;
+    let base_len = base.len();
+    drop(base);
+
+    // Locate the regions which changed, the verification ensured they are at the same
+    // offsets in both files
+    let cursor = &mut Cursor::new(&contributed[..]);
+    read_header::<E, _>(cursor)?;
+    let vk_start = cursor.position() as usize;
+    VerifyingKey::<E>::deserialize(&mut *cursor)?;
+    // delta_g2 follows alpha_g1, beta_g2 and gamma_g2
+    let delta_g2_start = vk_start + E::G1Affine::SERIALIZED_SIZE + 2 * E::G2Affine::SERIALIZED_SIZE;
+    // delta_g1 follows beta_g1
+    let delta_g1_start = cursor.position() as usize + E::G1Affine::SERIALIZED_SIZE;
+    cursor.seek(SeekFrom::Current(2 * E::G1Affine::SERIALIZED_SIZE as i64))?;
+    skip_vec::<E::G1Affine, _>(cursor)?; // Alpha G1
+    skip_vec::<E::G1Affine, _>(cursor)?; // Beta G1
+    skip_vec::<E::G2Affine, _>(cursor)?; // Beta G2
+    let queries_start = cursor.position() as usize;
+    skip_vec::<E::G1Affine, _>(cursor)?; // H
+    skip_vec::<E::G1Affine, _>(cursor)?; // L
+    let queries_end = cursor.position() as usize;
+    // skip the cs_hash and the phase 1 digest
+    let contributions_start = queries_end + 2 * HASH_LEN;
+
+    // If `out_path` is `base_path` under another name, replace the file itself rather
+    // than e.g. a symlink to it
+    let out_path = match (fs::canonicalize(base_path), fs::canonicalize(out_path)) {
+        (Ok(base), Ok(out)) if base == out => base,
+        _ => out_path.to_path_buf(),
+    };
+    // The output is written next to `out_path` and only renamed into place once it is
+    // complete, so that an interruption never leaves a partially updated file
+    let mut tmp_path = out_path.clone().into_os_string();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+    let written = (|| -> Result<()> {
+        fs::copy(base_path, &tmp_path)?;
+        let mut out = OpenOptions::new().write(true).open(&tmp_path)?;
+        for region in &[
+            delta_g2_start..delta_g2_start + E::G2Affine::SERIALIZED_SIZE,
+            delta_g1_start..delta_g1_start + E::G1Affine::SERIALIZED_SIZE,
+            queries_start..queries_end,
+            // the number of contributions
+            contributions_start..contributions_start + 4,
+            // the new contributions
+            base_len..contributed.len(),
+        ] {
+            out.seek(SeekFrom::Start(region.start as u64))?;
+            out.write_all(&contributed[region.clone()])?;
+        }
+        out.sync_all()?;
+        Ok(())
+    })();
+    if let Err(err) = written {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(err);
+    }
+    fs::rename(&tmp_path, &out_path)?;
+
+    info!("done.");
+
+    Ok(hashes)
+}
+
+/// Skips the vector ahead of the cursor and returns its length.
+fn skip_vec<C: AffineCurve, B: Read + Seek>(buffer: &mut B) -> Result<usize> {
     let len = u64::deserialize(buffer)? as usize;
     let skip_len = len * C::SERIALIZED_SIZE;
     buffer.seek(SeekFrom::Current(skip_len as i64))?;
-    Ok(())
+    Ok(len)
 }
 
 /// Multiplies a vector of affine elements by `element` in `batch_size` batches
@@ -424,7 +881,7 @@ fn chunked_ensure_unchanged_vec<C: AffineCurve>(
         let _enter = span1.enter();
 
         let (els_before, els_after) = read_batch::<C, _>(before, after, batch_size)?;
-        ensure_unchanged_vec(&els_before, &els_after, kind)?;
+        ensure_unchanged_vec_at_offset(&els_before, &els_after, i * batch_size, kind)?;
 
         trace!("ok");
     }
@@ -435,7 +892,7 @@ fn chunked_ensure_unchanged_vec<C: AffineCurve>(
         let _enter = span1.enter();
 
         let (els_before, els_after) = read_batch::<C, _>(before, after, leftovers)?;
-        ensure_unchanged_vec(&els_before, &els_after, kind)?;
+        ensure_unchanged_vec_at_offset(&els_before, &els_after, iters * batch_size, kind)?;
 
         trace!("ok");
     }
@@ -445,7 +902,8 @@ fn chunked_ensure_unchanged_vec<C: AffineCurve>(
     Ok(())
 }
 
-/// Checks that 2 vectors read from the 2 buffers are the same in chunks
+/// Checks that 2 vectors read from the 2 buffers have the same ratio in chunks,
+/// and that none of the `after` elements are the point at infinity
 fn chunked_check_ratio<E: PairingEngine>(
     before: &mut [u8],
     before_delta_g2: E::G2Affine,
@@ -472,12 +930,14 @@ fn chunked_check_ratio<E: PairingEngine>(
     let leftovers = len_before % batch_size;
     for _ in 0..iters {
         let (els_before, els_after) = read_batch::<E::G1Affine, _>(before, after, batch_size)?;
+        ensure_dense(&els_after)?;
         let pairs = merge_pairs(&els_before, &els_after);
         check_same_ratio::<E>(&pairs, &(after_delta_g2, before_delta_g2), err)?;
     }
     // in case the batch size did not evenly divide the number of queries
     if leftovers > 0 {
         let (els_before, els_after) = read_batch::<E::G1Affine, _>(before, after, leftovers)?;
+        ensure_dense(&els_after)?;
         let pairs = merge_pairs(&els_before, &els_after);
         check_same_ratio::<E>(&pairs, &(after_delta_g2, before_delta_g2), err)?;
     }
@@ -1,2 +1,25 @@
+#[cfg(not(feature = "wasm"))]
+pub mod powers_of_tau;
+
 #[cfg(any(test, feature = "testing"))]
 pub mod testing;
+
+use setup_utils::{InvariantKind, Phase2Error, Result, HASH_LEN};
+
+use std::io::Read;
+use subtle::ConstantTimeEq;
+
+/// Same as `parameters::ensure_unchanged` for `HASH_LEN` byte hashes, which are compared in constant time.
+pub fn ensure_unchanged_hash(before: &[u8; HASH_LEN], after: &[u8; HASH_LEN], kind: InvariantKind) -> Result<()> {
+    if !bool::from(before[..].ct_eq(&after[..])) {
+        return Err(Phase2Error::BrokenInvariant(kind).into());
+    }
+    Ok(())
+}
+
+/// Same as `read_exact`, but a failure reports `context` as what was being read
+pub(crate) fn read_exact_ctx<R: Read>(reader: &mut R, buf: &mut [u8], context: &'static str) -> Result<()> {
+    reader
+        .read_exact(buf)
+        .map_err(|cause| Phase2Error::Io { context, cause }.into())
+}
@@ -0,0 +1,153 @@
+use setup_utils::{
+    buffer_size,
+    BatchDeserializer,
+    CheckForCorrectness,
+    Deserializer,
+    Error,
+    Groth16Params,
+    Phase2Error,
+    Result,
+    UseCompression,
+};
+
+use snarkvm_curves::PairingEngine;
+
+use memmap::MmapOptions;
+use std::{fs::OpenOptions, path::Path};
+
+/// Size of the hash at the start of a Phase 1 response
+const PHASE1_HASH_SIZE: usize = 64;
+
+/// Builds the Groth16 parameters of size `phase2_size` directly from a Phase 1 response
+/// file of 2^`phase1_powers` powers of tau.
+///
+/// The file is memory mapped and only the prefix of each section needed for `phase2_size`
+/// is deserialized, so the accumulator never has to be loaded in memory as a whole.
+pub fn groth16_params_from_phase1_file<E: PairingEngine>(
+    path: &Path,
+    compressed: UseCompression,
+    check_input_for_correctness: CheckForCorrectness,
+    phase1_powers: usize,
+    phase2_size: usize,
+) -> Result<Groth16Params<E>> {
+    let g1_size = buffer_size::<E::G1Affine>(compressed);
+    let g2_size = buffer_size::<E::G2Affine>(compressed);
+
+    if phase2_size == 0 {
+        return Err(Phase2Error::PhaseSizeTooSmall { needed: 1, got: 0 }.into());
+    }
+
+    let other_length = 1 << phase1_powers;
+    let g1_length = (other_length << 1) - 1;
+    if phase2_size > other_length {
+        return Err(Error::InvalidLength {
+            expected: other_length,
+            got: phase2_size,
+        });
+    }
+
+    let file = OpenOptions::new().read(true).open(path)?;
+    let map = unsafe { MmapOptions::new().map(&file)? };
+
+    let accumulator_length = PHASE1_HASH_SIZE + (g1_length + 2 * other_length) * g1_size + (other_length + 1) * g2_size;
+    if map.len() < accumulator_length {
+        return Err(Error::InvalidLength {
+            expected: accumulator_length,
+            got: map.len(),
+        });
+    }
+
+    // Locate the sections of the accumulator, in the order they are serialized
+    let tau_g1_start = PHASE1_HASH_SIZE;
+    let tau_g2_start = tau_g1_start + g1_length * g1_size;
+    let alpha_g1_start = tau_g2_start + other_length * g2_size;
+    let beta_g1_start = alpha_g1_start + other_length * g1_size;
+    let beta_g2_start = beta_g1_start + other_length * g1_size;
+
+    // The H query needs twice as many powers of tau in G1
+    let tau_g1_length = (phase2_size << 1) - 1;
+    let tau_powers_g1 = map[tau_g1_start..tau_g1_start + tau_g1_length * g1_size]
+        .read_batch(compressed, check_input_for_correctness)?;
+    let tau_powers_g2 =
+        map[tau_g2_start..tau_g2_start + phase2_size * g2_size].read_batch(compressed, check_input_for_correctness)?;
+    let alpha_tau_powers_g1 = map[alpha_g1_start..alpha_g1_start + phase2_size * g1_size]
+        .read_batch(compressed, check_input_for_correctness)?;
+    let beta_tau_powers_g1 = map[beta_g1_start..beta_g1_start + phase2_size * g1_size]
+        .read_batch(compressed, check_input_for_correctness)?;
+    let beta_g2 =
+        (&map[beta_g2_start..beta_g2_start + g2_size]).read_element(compressed, check_input_for_correctness)?;
+
+    Groth16Params::new(
+        phase2_size,
+        tau_powers_g1,
+        tau_powers_g2,
+        alpha_tau_powers_g1,
+        beta_tau_powers_g1,
+        beta_g2,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phase1::{helpers::testing::setup_verify, Phase1, Phase1Parameters, ProvingSystem};
+    use snarkvm_curves::bls12_377::Bls12_377;
+
+    use std::fs;
+
+    #[test]
+    fn groth16_params_from_phase1_file() {
+        groth16_params_from_phase1_file_curve::<Bls12_377>(UseCompression::No);
+        groth16_params_from_phase1_file_curve::<Bls12_377>(UseCompression::Yes);
+    }
+
+    fn groth16_params_from_phase1_file_curve<E: PairingEngine>(compressed: UseCompression) {
+        let powers = 5;
+        let batch = 16;
+        let phase2_size = 8;
+        let params = Phase1Parameters::<E>::new_full(ProvingSystem::Groth16, powers, batch);
+        let (_, output, _, _) = setup_verify(compressed, CheckForCorrectness::Full, compressed, &params);
+
+        let path = std::env::temp_dir().join(format!("phase1_response_{:?}_{}", compressed, std::process::id()));
+        fs::write(&path, &output).unwrap();
+        let streamed = super::groth16_params_from_phase1_file::<E>(
+            &path,
+            compressed,
+            CheckForCorrectness::Full,
+            powers,
+            phase2_size,
+        )
+        .unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let accumulator = Phase1::deserialize(&output, compressed, CheckForCorrectness::Full, &params).unwrap();
+        let expected = Groth16Params::<E>::new(
+            phase2_size,
+            accumulator.tau_powers_g1,
+            accumulator.tau_powers_g2,
+            accumulator.alpha_tau_powers_g1,
+            accumulator.beta_tau_powers_g1,
+            accumulator.beta_g2,
+        )
+        .unwrap();
+        assert_eq!(streamed, expected);
+    }
+
+    #[test]
+    fn zero_phase2_size() {
+        // rejected before the file is even opened
+        let path = std::env::temp_dir().join(format!("phase1_response_missing_{}", std::process::id()));
+        let err = super::groth16_params_from_phase1_file::<Bls12_377>(
+            &path,
+            UseCompression::No,
+            CheckForCorrectness::Full,
+            5,
+            0,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::PhaseSizeTooSmall { needed: 1, got: 0 })
+        ));
+    }
+}
@@ -2,26 +2,123 @@
 //!
 //! A Groth16 keypair. Generate one with the Keypair::new method.
 //! Dispose of the private key ASAP once it's been used.
-use setup_utils::{CheckForCorrectness, Deserializer, HashWriter, Result, Serializer, UseCompression};
+use crate::helpers::{ensure_unchanged_hash, read_exact_ctx};
+use setup_utils::{
+    check_same_ratio,
+    CheckForCorrectness,
+    Deserializer,
+    Error,
+    HashWriter,
+    InvariantKind,
+    Phase2Error,
+    Result,
+    Serializer,
+    UseCompression,
+    HASH_LEN,
+};
 use snarkvm_curves::{PairingEngine, ProjectiveCurve};
-use snarkvm_utilities::{CanonicalSerialize, ConstantSerializedSize, UniformRand};
+use snarkvm_fields::{Field, Zero};
+use snarkvm_utilities::{CanonicalDeserialize, CanonicalSerialize, ConstantSerializedSize, UniformRand};
 
-use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
-use rand::Rng;
+use byteorder::{BigEndian, WriteBytesExt};
+use rand::{CryptoRng, Rng, RngCore};
+use serde::de;
 use snarkvm_algorithms::hash_to_curve::hash_to_curve;
 use std::{
     fmt,
     io::{self, Read, Write},
-    ops::Mul,
+    ops::{Deref, DerefMut, Mul},
+    ptr,
+    sync::atomic,
 };
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// This needs to be destroyed by at least one participant
 /// for the final parameters to be secure.
+///
+/// The delta is overwritten with zero when the key is dropped.
 pub struct PrivateKey<E: PairingEngine> {
     pub delta: E::Fr,
 }
 
-pub const PUBKEY_SIZE: usize = 544; // 96 * 2 + 48 * 2 * 3 + 64, assuming uncompressed elements
+impl<E: PairingEngine> Zeroize for PrivateKey<E> {
+    fn zeroize(&mut self) {
+        zeroize_scalar(&mut self.delta);
+    }
+}
+
+impl<E: PairingEngine> Drop for PrivateKey<E> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<E: PairingEngine> ZeroizeOnDrop for PrivateKey<E> {}
+
+/// Overwrites a secret scalar with zero. snarkVM's field elements do not
+/// implement `Zeroize`, so this performs the volatile write itself.
+pub fn zeroize_scalar<F: Field>(scalar: &mut F) {
+    // Safety: `scalar` is a valid and aligned mutable reference
+    unsafe { ptr::write_volatile(scalar, F::zero()) };
+    atomic::compiler_fence(atomic::Ordering::SeqCst);
+}
+
+impl<E: PairingEngine> PrivateKey<E> {
+    /// Returns the inverse of the delta, which the H and L queries are multiplied by
+    pub fn delta_inv(&self) -> SecretScalar<E::Fr> {
+        SecretScalar::new(self.delta.inverse().expect("keypairs never have a zero delta"))
+    }
+}
+
+/// A secret scalar, e.g. a contribution's delta or its inverse, which is
+/// overwritten with zero when it goes out of scope. Unlike calling
+/// `zeroize_scalar` once done with it, this also covers early returns.
+pub struct SecretScalar<F: Field>(F);
+
+impl<F: Field> SecretScalar<F> {
+    pub fn new(scalar: F) -> Self {
+        Self(scalar)
+    }
+}
+
+impl<F: Field> Deref for SecretScalar<F> {
+    type Target = F;
+
+    fn deref(&self) -> &F {
+        &self.0
+    }
+}
+
+impl<F: Field> DerefMut for SecretScalar<F> {
+    fn deref_mut(&mut self) -> &mut F {
+        &mut self.0
+    }
+}
+
+impl<F: Field> Drop for SecretScalar<F> {
+    fn drop(&mut self) {
+        zeroize_scalar(&mut self.0);
+    }
+}
+
+impl<F: Field> ZeroizeOnDrop for SecretScalar<F> {}
+
+/// A cryptographically secure RNG which can be used as a trait object, so that
+/// RNGs of different types can be combined by `Keypair::new_composed`.
+pub trait CryptoRngCore: RngCore + CryptoRng {}
+
+impl<R: RngCore + CryptoRng> CryptoRngCore for R {}
+
+/// The size of a serialized BLS12-377 `PublicKey` without an identity, use `PublicKey::size`
+/// for other curves. It ends with the flag telling that there is no identity.
+pub const PUBKEY_SIZE: usize = 480 + HASH_LEN + 1; // 96 * 3 + 192 + HASH_LEN + 1, assuming uncompressed elements
+
+/// Upper bound on the number of contributions `PublicKey::read_batch` accepts, so that
+/// a malicious length prefix is rejected before anything is read.
+pub const MAX_CONTRIBUTIONS: usize = 1 << 20;
+
+/// Upper bound on the length of a contributor's identity.
+pub const MAX_IDENTITY_LEN: usize = 1024;
 
 /// This allows others to verify that you contributed. The hash produced
 /// by `MPCParameters::contribute` is just a BLAKE2b hash of this object.
@@ -42,21 +139,35 @@ pub struct PublicKey<E: PairingEngine> {
     pub r_delta: E::G2Affine,
 
     /// Hash of the transcript (used for mapping to r)
-    pub transcript: [u8; 64],
+    pub transcript: [u8; HASH_LEN],
+
+    /// Optional name of the contributor, hashed into the transcript so that
+    /// it cannot be changed without breaking verification. It is private so
+    /// that it is always at most `MAX_IDENTITY_LEN` bytes long.
+    identity: Option<Vec<u8>>,
 }
 
 impl<E: PairingEngine> PublicKey<E> {
     /// Returns the Blake2b hash of the public key
-    pub fn hash(&self) -> [u8; 64] {
+    pub fn hash(&self) -> [u8; HASH_LEN] {
         let sink = io::sink();
         let mut sink = HashWriter::new(sink);
-        self.write(&mut sink).unwrap();
-        let h = sink.into_hash();
-        let mut response = [0u8; 64];
-        response.copy_from_slice(h.as_ref());
-        response
+        self.write_hashed(&mut sink).unwrap();
+        sink.into_digest()
+    }
+
+    /// Returns the contributor's identity, if they recorded one
+    pub fn identity(&self) -> Option<&[u8]> {
+        self.identity.as_deref()
+    }
+
+    /// Replaces the identity without updating the transcript
+    #[cfg(any(test, feature = "testing"))]
+    pub fn set_identity(&mut self, identity: Option<Vec<u8>>) {
+        self.identity = identity;
     }
 
+    /// Writes the public keys prefixed by their number.
     pub fn write_batch<W: Write>(writer: &mut W, pubkeys: &[PublicKey<E>]) -> Result<()> {
         writer.write_u32::<BigEndian>(pubkeys.len() as u32)?;
         for pubkey in pubkeys {
@@ -65,22 +176,140 @@ impl<E: PairingEngine> PublicKey<E> {
         Ok(())
     }
 
+    /// Returns the number of bytes `write_batch` writes for `pubkeys`.
+    pub fn batch_size(pubkeys: &[PublicKey<E>]) -> usize {
+        4 + pubkeys.iter().map(|pubkey| pubkey.serialized_size()).sum::<usize>()
+    }
+
     pub fn read_batch<R: Read>(reader: &mut R) -> Result<Vec<Self>> {
-        let mut contributions = vec![];
-        let contributions_len = reader.read_u32::<BigEndian>()? as usize;
+        Self::read_batch_with_progress(reader, |_, _| ())
+    }
+
+    /// Same as `read_batch`, but calls `progress(keys_done, keys_total)` after
+    /// each public key has been read. The total is the batch's length prefix.
+    pub fn read_batch_with_progress<R: Read, F: FnMut(usize, usize)>(
+        reader: &mut R,
+        mut progress: F,
+    ) -> Result<Vec<Self>> {
+        let contributions_len = Self::read_batch_len(reader)?;
+        // the length is read from the input, so grow the vector as keys are
+        // read rather than allocating all of them upfront
+        let mut contributions = Vec::new();
         for _ in 0..contributions_len {
             contributions.push(PublicKey::read(reader)?);
+            progress(contributions.len(), contributions_len);
         }
         Ok(contributions)
     }
 
+    /// Reads a batch written before identities were introduced, in which
+    /// each key only consists of its points.
+    pub(crate) fn read_batch_legacy<R: Read>(reader: &mut R) -> Result<Vec<Self>> {
+        let contributions_len = Self::read_batch_len(reader)?;
+        let mut contributions = Vec::new();
+        for _ in 0..contributions_len {
+            contributions.push(PublicKey::read_points(reader)?);
+        }
+        Ok(contributions)
+    }
+
+    fn read_batch_len<R: Read>(reader: &mut R) -> Result<usize> {
+        let mut contributions_len = [0u8; 4];
+        read_exact_ctx(reader, &mut contributions_len, "contributions batch")?;
+        let contributions_len = u32::from_be_bytes(contributions_len) as usize;
+        if contributions_len > MAX_CONTRIBUTIONS {
+            return Err(Phase2Error::InvalidLength.into());
+        }
+        Ok(contributions_len)
+    }
+
+    /// Checks that the transcript of this public key follows `cs_hash` and the
+    /// `prior_contributions`, and that its signature of knowledge of delta is
+    /// valid. This is cheap compared to checking the contribution against the
+    /// parameters before it, but it does not check `delta_after`.
+    pub fn verify_signature_of_knowledge(
+        &self,
+        cs_hash: [u8; HASH_LEN],
+        prior_contributions: &[PublicKey<E>],
+    ) -> Result<()> {
+        self.signature_of_knowledge_point(cs_hash, prior_contributions)?;
+        Ok(())
+    }
+
+    /// Same as `verify_signature_of_knowledge`, but returns the G2 point `r`
+    /// which was hashed from the transcript.
+    pub(crate) fn signature_of_knowledge_point(
+        &self,
+        cs_hash: [u8; HASH_LEN],
+        prior_contributions: &[PublicKey<E>],
+    ) -> Result<E::G2Affine> {
+        let r = self.transcript_point(cs_hash, prior_contributions)?;
+
+        // Check the signature of knowledge
+        check_same_ratio::<E>(
+            &(self.s, self.s_delta),
+            &(r, self.r_delta),
+            "Incorrect signature of knowledge",
+        )?;
+
+        Ok(r)
+    }
+
+    /// Checks the key's transcript against the prior contributions and
+    /// returns the G2 point `r` which is hashed from it, without checking
+    /// the signature of knowledge.
+    pub(crate) fn transcript_point(
+        &self,
+        cs_hash: [u8; HASH_LEN],
+        prior_contributions: &[PublicKey<E>],
+    ) -> Result<E::G2Affine> {
+        let hash = hash_cs_pubkeys(
+            cs_hash,
+            prior_contributions,
+            self.s,
+            self.s_delta,
+            self.identity.as_deref(),
+        );
+        ensure_unchanged_hash(&self.transcript, &hash, InvariantKind::Transcript)?;
+
+        // generate the G2 point from the hash
+        Ok(hash_to_curve::<E::G2Affine>(&hex::encode(hash.as_ref())).0)
+    }
+
+    /// The size of a serialized key without an identity. A key with an identity
+    /// takes 4 more bytes for its length, followed by the identity itself.
     pub fn size() -> usize {
-        3 * E::G1Affine::UNCOMPRESSED_SIZE + E::G2Affine::UNCOMPRESSED_SIZE + 64
+        Self::points_size() + 1
+    }
+
+    fn points_size() -> usize {
+        3 * E::G1Affine::UNCOMPRESSED_SIZE + E::G2Affine::UNCOMPRESSED_SIZE + HASH_LEN
+    }
+
+    /// Returns the number of bytes `write` writes for this key.
+    pub fn serialized_size(&self) -> usize {
+        Self::size() + self.identity.as_ref().map_or(0, |identity| 4 + identity.len())
     }
 
     /// Serializes the key's **uncompressed** points to the provided
-    /// writer
+    /// writer, followed by its identity (see `write_identity`).
     pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.write_points(writer)?;
+        self.write_identity(writer)
+    }
+
+    /// Writes what the key's hash and the transcripts of later contributions
+    /// commit to: its points, followed by its identity only if it has one.
+    /// Keys without an identity are thus hashed like before identities existed.
+    fn write_hashed<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.write_points(writer)?;
+        if self.identity.is_some() {
+            self.write_identity(writer)?;
+        }
+        Ok(())
+    }
+
+    fn write_points<W: Write>(&self, writer: &mut W) -> Result<()> {
         self.delta_after.serialize_uncompressed(writer)?;
         self.s.serialize_uncompressed(writer)?;
         self.s_delta.serialize_uncompressed(writer)?;
@@ -89,15 +318,57 @@ impl<E: PairingEngine> PublicKey<E> {
         Ok(())
     }
 
-    /// Reads the key's **uncompressed** points from the provided
-    /// reader
+    /// Writes a flag telling whether the key has an identity, followed by
+    /// its length and bytes if it does.
+    fn write_identity<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match &self.identity {
+            Some(identity) if identity.len() > MAX_IDENTITY_LEN => {
+                return Err(Error::InvalidLength {
+                    expected: MAX_IDENTITY_LEN,
+                    got: identity.len(),
+                })
+            }
+            Some(identity) => {
+                writer.write_u8(1)?;
+                writer.write_u32::<BigEndian>(identity.len() as u32)?;
+                writer.write_all(identity)?;
+            }
+            None => writer.write_u8(0)?,
+        }
+        Ok(())
+    }
+
+    fn read_identity<R: Read>(reader: &mut R) -> Result<Option<Vec<u8>>> {
+        let mut has_identity = [0u8; 1];
+        read_exact_ctx(reader, &mut has_identity, "contribution identity")?;
+        if has_identity[0] == 0 {
+            return Ok(None);
+        }
+        let mut identity_len = [0u8; 4];
+        read_exact_ctx(reader, &mut identity_len, "contribution identity")?;
+        let identity_len = u32::from_be_bytes(identity_len) as usize;
+        if identity_len > MAX_IDENTITY_LEN {
+            return Err(Phase2Error::InvalidLength.into());
+        }
+        let mut identity = vec![0u8; identity_len];
+        read_exact_ctx(reader, &mut identity, "contribution identity")?;
+        Ok(Some(identity))
+    }
+
+    /// Reads a key written by `write` from the provided reader.
     pub fn read<R: Read>(reader: &mut R) -> Result<PublicKey<E>> {
+        let mut pubkey = Self::read_points(reader)?;
+        pubkey.identity = Self::read_identity(reader)?;
+        Ok(pubkey)
+    }
+
+    fn read_points<R: Read>(reader: &mut R) -> Result<PublicKey<E>> {
         let delta_after = reader.read_element(UseCompression::No, CheckForCorrectness::Full)?;
         let s = reader.read_element(UseCompression::No, CheckForCorrectness::Full)?;
         let s_delta = reader.read_element(UseCompression::No, CheckForCorrectness::Full)?;
         let r_delta = reader.read_element(UseCompression::No, CheckForCorrectness::Full)?;
-        let mut transcript = [0u8; 64];
-        reader.read_exact(&mut transcript)?;
+        let mut transcript = [0u8; HASH_LEN];
+        read_exact_ctx(reader, &mut transcript, "contribution transcript")?;
 
         Ok(PublicKey {
             delta_after,
@@ -105,6 +376,7 @@ impl<E: PairingEngine> PublicKey<E> {
             s_delta,
             r_delta,
             transcript,
+            identity: None,
         })
     }
 }
@@ -120,9 +392,96 @@ impl<E: PairingEngine> Keypair<E> {
     /// Compute a keypair, given the current parameters. Keypairs
     /// cannot be reused for multiple contributions or contributions
     /// in different parameters.
-    pub fn new(delta_g1: E::G1Affine, cs_hash: [u8; 64], contributions: &[PublicKey<E>], rng: &mut impl Rng) -> Self {
+    pub fn new(
+        delta_g1: E::G1Affine,
+        cs_hash: [u8; HASH_LEN],
+        contributions: &[PublicKey<E>],
+        rng: &mut impl Rng,
+    ) -> Self {
         // Sample random delta -- THIS MUST BE DESTROYED
-        let delta: E::Fr = E::Fr::rand(rng);
+        let delta = SecretScalar::new(E::Fr::rand(rng));
+        Self::with_delta(delta_g1, cs_hash, contributions, *delta, None, rng)
+    }
+
+    /// Same as `new`, but the public key records the contributor's `identity`,
+    /// e.g. their name, which is hashed into the transcript.
+    pub fn new_with_identity(
+        delta_g1: E::G1Affine,
+        cs_hash: [u8; HASH_LEN],
+        contributions: &[PublicKey<E>],
+        identity: Vec<u8>,
+        rng: &mut impl Rng,
+    ) -> Result<Self> {
+        if identity.len() > MAX_IDENTITY_LEN {
+            return Err(Error::InvalidLength {
+                expected: MAX_IDENTITY_LEN,
+                got: identity.len(),
+            });
+        }
+        let delta = SecretScalar::new(E::Fr::rand(rng));
+        Ok(Self::with_delta(
+            delta_g1,
+            cs_hash,
+            contributions,
+            *delta,
+            Some(identity),
+            rng,
+        ))
+    }
+
+    /// Same as `new`, but one delta is sampled from each of `rngs` and the
+    /// private delta is their product, so that the delta is unpredictable as
+    /// long as one of the RNGs is. The first RNG is also used for the rest of
+    /// the public key.
+    pub fn new_composed(
+        delta_g1: E::G1Affine,
+        cs_hash: [u8; HASH_LEN],
+        contributions: &[PublicKey<E>],
+        rngs: &mut [&mut dyn CryptoRngCore],
+    ) -> Result<Self> {
+        let (first, rest) = rngs.split_first_mut().ok_or(Phase2Error::NoRandomness)?;
+        let mut delta = SecretScalar::new(E::Fr::rand(first));
+        for rng in rest.iter_mut() {
+            let share = SecretScalar::new(E::Fr::rand(rng));
+            *delta *= &*share;
+        }
+        Ok(Self::with_delta(delta_g1, cs_hash, contributions, *delta, None, first))
+    }
+
+    /// Same as `new`, but the private delta is read from `delta_bytes` instead of
+    /// being sampled, e.g. when it was generated by an HSM. The bytes must be the
+    /// canonical serialization of a non-zero field element, so zero and out of
+    /// range values are rejected. `rng` is still used for the rest of the public key.
+    pub fn from_bytes(
+        delta_g1: E::G1Affine,
+        cs_hash: [u8; HASH_LEN],
+        contributions: &[PublicKey<E>],
+        delta_bytes: &[u8],
+        rng: &mut impl Rng,
+    ) -> Result<Self> {
+        let reader = &mut &delta_bytes[..];
+        let delta = SecretScalar::new(E::Fr::deserialize(reader)?);
+        if !reader.is_empty() {
+            return Err(Error::InvalidLength {
+                expected: delta_bytes.len() - reader.len(),
+                got: delta_bytes.len(),
+            });
+        }
+        // a zero delta would erase the previous contributions and has no inverse
+        if delta.is_zero() {
+            return Err(Phase2Error::ZeroDelta.into());
+        }
+        Ok(Self::with_delta(delta_g1, cs_hash, contributions, *delta, None, rng))
+    }
+
+    fn with_delta(
+        delta_g1: E::G1Affine,
+        cs_hash: [u8; HASH_LEN],
+        contributions: &[PublicKey<E>],
+        delta: E::Fr,
+        identity: Option<Vec<u8>>,
+        rng: &mut impl Rng,
+    ) -> Self {
         let delta_after = delta_g1.mul(delta);
 
         // Compute delta s-pair in G1
@@ -130,7 +489,7 @@ impl<E: PairingEngine> Keypair<E> {
         let s_delta = s.mul(delta);
 
         // Get the transcript
-        let transcript = hash_cs_pubkeys(cs_hash, contributions, s, s_delta);
+        let transcript = hash_cs_pubkeys(cs_hash, contributions, s, s_delta, identity.as_deref());
         // Compute delta s-pair in G2 by hashing the transcript and multiplying it by delta
         let r = hash_to_curve::<E::G2Affine>(&hex::encode(transcript[..].as_ref())).0;
         let r_delta = r.mul(delta);
@@ -142,6 +501,7 @@ impl<E: PairingEngine> Keypair<E> {
                 s_delta,
                 r_delta,
                 transcript,
+                identity,
             },
             private_key: PrivateKey { delta },
         }
@@ -150,47 +510,124 @@ impl<E: PairingEngine> Keypair<E> {
 
 /// Returns the transcript hash so far.
 ///
-/// Internally calculates: `H(cs_hash | <contributions> | s | s_delta)`
+/// Internally calculates: `H(cs_hash | <contributions> | s | s_delta | identity)`,
+/// where the identity is only hashed if there is one, as its length followed by
+/// its bytes.
 pub fn hash_cs_pubkeys<E: PairingEngine>(
-    cs_hash: [u8; 64],
+    cs_hash: [u8; HASH_LEN],
     contributions: &[PublicKey<E>],
     s: E::G1Affine,
     s_delta: E::G1Affine,
-) -> [u8; 64] {
-    let h = {
-        let sink = io::sink();
-        let mut sink = HashWriter::new(sink);
-
-        sink.write_all(&cs_hash[..]).unwrap();
-        for pubkey in contributions {
-            pubkey.write(&mut sink).unwrap();
-        }
-        // Write s and s_delta!
-        sink.write_element(&s, UseCompression::Yes).unwrap();
-        sink.write_element(&s_delta, UseCompression::Yes).unwrap();
-        sink.into_hash()
-    };
-    // This avoids making a weird assumption about the hash into the
-    // group.
-    let mut transcript = [0; 64];
-    transcript.copy_from_slice(h.as_ref());
-    transcript
+    identity: Option<&[u8]>,
+) -> [u8; HASH_LEN] {
+    let sink = io::sink();
+    let mut sink = HashWriter::new(sink);
+
+    sink.write_all(&cs_hash[..]).unwrap();
+    for pubkey in contributions {
+        pubkey.write_hashed(&mut sink).unwrap();
+    }
+    // Write s and s_delta!
+    sink.write_element(&s, UseCompression::Yes).unwrap();
+    sink.write_element(&s_delta, UseCompression::Yes).unwrap();
+    if let Some(identity) = identity {
+        sink.write_u32::<BigEndian>(identity.len() as u32).unwrap();
+        sink.write_all(identity).unwrap();
+    }
+    sink.into_digest()
 }
 
 impl<E: PairingEngine> fmt::Debug for PublicKey<E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "PublicKey {{ delta_after: {}, s: {:?}, s_delta: {:?} r_delta: {:?}, transcript : {:?}}}",
+            "PublicKey {{ delta_after: {}, s: {:?}, s_delta: {:?} r_delta: {:?}, transcript : {:?}, identity: {:?}}}",
             self.delta_after,
             self.s,
             self.s_delta,
             self.r_delta,
-            &self.transcript[..]
+            &self.transcript[..],
+            self.identity
         )
     }
 }
 
+/// The JSON representation of a `PublicKey`, with hex encoded compressed points
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PublicKeyJson {
+    delta_after: String,
+    s: String,
+    s_delta: String,
+    r_delta: String,
+    transcript: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    identity: Option<String>,
+}
+
+fn to_hex<T: CanonicalSerialize>(element: &T) -> String {
+    let mut serialized = vec![];
+    element
+        .serialize(&mut serialized)
+        .expect("serializing into a vector cannot fail");
+    hex::encode(serialized)
+}
+
+fn from_hex<T: CanonicalDeserialize, D: de::Error>(encoded: &str) -> std::result::Result<T, D> {
+    let bytes = hex::decode(encoded).map_err(D::custom)?;
+    T::deserialize(&mut &bytes[..]).map_err(D::custom)
+}
+
+impl<E: PairingEngine> serde::Serialize for PublicKey<E> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let json = PublicKeyJson {
+            delta_after: to_hex(&self.delta_after),
+            s: to_hex(&self.s),
+            s_delta: to_hex(&self.s_delta),
+            r_delta: to_hex(&self.r_delta),
+            transcript: hex::encode(&self.transcript[..]),
+            identity: self.identity.as_ref().map(hex::encode),
+        };
+        serde::Serialize::serialize(&json, serializer)
+    }
+}
+
+impl<'de, E: PairingEngine> serde::Deserialize<'de> for PublicKey<E> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let json: PublicKeyJson = serde::Deserialize::deserialize(deserializer)?;
+        let mut transcript = [0u8; HASH_LEN];
+        let transcript_bytes = hex::decode(&json.transcript).map_err(de::Error::custom)?;
+        if transcript_bytes.len() != transcript.len() {
+            return Err(de::Error::invalid_length(
+                transcript_bytes.len(),
+                &"a HASH_LEN byte transcript",
+            ));
+        }
+        transcript.copy_from_slice(&transcript_bytes);
+
+        let identity = json
+            .identity
+            .map(|identity| hex::decode(identity).map_err(de::Error::custom))
+            .transpose()?;
+        if let Some(identity) = &identity {
+            if identity.len() > MAX_IDENTITY_LEN {
+                return Err(de::Error::invalid_length(
+                    identity.len(),
+                    &"an identity of at most MAX_IDENTITY_LEN bytes",
+                ));
+            }
+        }
+
+        Ok(PublicKey {
+            delta_after: from_hex(&json.delta_after)?,
+            s: from_hex(&json.s)?,
+            s_delta: from_hex(&json.s_delta)?,
+            r_delta: from_hex(&json.r_delta)?,
+            transcript,
+            identity,
+        })
+    }
+}
+
 impl<E: PairingEngine> PartialEq for PublicKey<E> {
     fn eq(&self, other: &PublicKey<E>) -> bool {
         self.delta_after == other.delta_after
@@ -198,33 +635,40 @@ impl<E: PairingEngine> PartialEq for PublicKey<E> {
             && self.s_delta == other.s_delta
             && self.r_delta == other.r_delta
             && &self.transcript[..] == other.transcript.as_ref()
+            && self.identity == other.identity
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use snarkvm_curves::{bls12_377::Bls12_377, AffineCurve};
+    use crate::parameters::verify_transcript;
+    use setup_utils::VerificationError;
+    use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, AffineCurve};
+    use snarkvm_fields::Zero;
 
     use rand::thread_rng;
 
     #[test]
     fn serialization() {
-        serialization_curve::<Bls12_377>()
+        serialization_curve::<Bls12_377>();
+        serialization_curve::<BW6_761>();
+
+        // 3 * 96 + 1 * 192 + HASH_LEN
+        assert_eq!(PublicKey::<Bls12_377>::size(), PUBKEY_SIZE);
     }
 
     fn serialization_curve<E: PairingEngine>() {
         let mut rng = thread_rng();
         let delta_g1 = E::G1Affine::prime_subgroup_generator();
 
-        let keypair = Keypair::<E>::new(delta_g1, [0; 64], &[], &mut rng);
+        let keypair = Keypair::<E>::new(delta_g1, [0; HASH_LEN], &[], &mut rng);
         let pubkey = keypair.public_key;
 
         let mut writer = vec![];
         pubkey.write(&mut writer).unwrap();
 
-        // 3 * 96 + 1 * 192 + 64
-        assert_eq!(writer.len(), 544);
+        assert_eq!(writer.len(), PublicKey::<E>::size());
 
         // try to read from it
         let mut reader = vec![0; writer.len()];
@@ -232,4 +676,191 @@ mod tests {
         let deserialized = PublicKey::<E>::read(&mut &reader[..]).unwrap();
         assert_eq!(deserialized, pubkey);
     }
+
+    #[test]
+    fn serde_json_round_trip() {
+        serde_json_round_trip_curve::<Bls12_377>();
+        serde_json_round_trip_curve::<BW6_761>();
+    }
+
+    fn serde_json_round_trip_curve<E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let delta_g1 = E::G1Affine::prime_subgroup_generator();
+        let first = Keypair::<E>::new(delta_g1, [0; HASH_LEN], &[], rng).public_key;
+        let second =
+            Keypair::<E>::new_with_identity(first.delta_after, [0; HASH_LEN], &[first.clone()], b"bob".to_vec(), rng)
+                .unwrap()
+                .public_key;
+
+        for pubkey in &[first, second] {
+            let json = serde_json::to_string(pubkey).unwrap();
+            let deserialized: PublicKey<E> = serde_json::from_str(&json).unwrap();
+            assert_eq!(&deserialized, pubkey);
+            assert_eq!(deserialized.hash(), pubkey.hash());
+        }
+
+        let json = serde_json::to_string(&Keypair::<E>::new(delta_g1, [0; HASH_LEN], &[], rng).public_key).unwrap();
+        // the transcript must be exactly HASH_LEN bytes
+        let too_long = json.replacen("\"transcript\":\"", "\"transcript\":\"00", 1);
+        assert!(serde_json::from_str::<PublicKey<E>>(&too_long).is_err());
+    }
+
+    #[test]
+    fn read_batch_with_progress() {
+        read_batch_with_progress_curve::<Bls12_377>()
+    }
+
+    fn read_batch_with_progress_curve<E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let delta_g1 = E::G1Affine::prime_subgroup_generator();
+        let pubkeys = (0..3)
+            .map(|_| Keypair::<E>::new(delta_g1, [0; HASH_LEN], &[], rng).public_key)
+            .collect::<Vec<_>>();
+        let mut writer = vec![];
+        PublicKey::write_batch(&mut writer, &pubkeys).unwrap();
+
+        let mut calls = vec![];
+        let deserialized =
+            PublicKey::<E>::read_batch_with_progress(&mut &writer[..], |done, total| calls.push((done, total)))
+                .unwrap();
+        assert_eq!(deserialized, pubkeys);
+        assert_eq!(calls, vec![(1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn batch_with_identity() {
+        batch_with_identity_curve::<Bls12_377>()
+    }
+
+    fn batch_with_identity_curve<E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let delta_g1 = E::G1Affine::prime_subgroup_generator();
+        let first = Keypair::<E>::new(delta_g1, [0; HASH_LEN], &[], rng).public_key;
+        let prior = [first];
+        let second = Keypair::<E>::new_with_identity(prior[0].delta_after, [0; HASH_LEN], &prior, b"bob".to_vec(), rng)
+            .unwrap()
+            .public_key;
+        let pubkeys = vec![prior[0].clone(), second];
+
+        let mut writer = vec![];
+        PublicKey::write_batch(&mut writer, &pubkeys).unwrap();
+        assert_eq!(writer.len(), PublicKey::batch_size(&pubkeys));
+        let deserialized = PublicKey::<E>::read_batch(&mut &writer[..]).unwrap();
+        assert_eq!(deserialized, pubkeys);
+        verify_transcript([0; HASH_LEN], &deserialized).unwrap();
+
+        // a key reads back with its identity
+        let mut writer = vec![];
+        pubkeys[1].write(&mut writer).unwrap();
+        assert_eq!(writer.len(), pubkeys[1].serialized_size());
+        assert_eq!(writer.len(), PublicKey::<E>::size() + 4 + 3);
+        assert_eq!(PublicKey::<E>::read(&mut &writer[..]).unwrap(), pubkeys[1]);
+
+        // keys without an identity all have the same size
+        let mut writer = vec![];
+        PublicKey::write_batch(&mut writer, &prior).unwrap();
+        assert_eq!(writer.len(), 4 + PublicKey::<E>::size());
+        assert_eq!(writer.len(), PublicKey::batch_size(&prior));
+
+        let too_long = vec![0; MAX_IDENTITY_LEN + 1];
+        assert!(Keypair::<E>::new_with_identity(delta_g1, [0; HASH_LEN], &[], too_long.clone(), rng).is_err());
+        let mut pubkey = pubkeys[1].clone();
+        pubkey.set_identity(Some(too_long));
+        let err = pubkey.write(&mut vec![]).unwrap_err();
+        assert!(matches!(err, Error::InvalidLength {
+            expected: MAX_IDENTITY_LEN,
+            ..
+        }));
+    }
+
+    #[test]
+    fn verify_signature_of_knowledge() {
+        verify_signature_of_knowledge_curve::<Bls12_377>()
+    }
+
+    fn verify_signature_of_knowledge_curve<E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let delta_g1 = E::G1Affine::prime_subgroup_generator();
+        let cs_hash = [1; HASH_LEN];
+        let first = Keypair::<E>::new(delta_g1, cs_hash, &[], rng).public_key;
+        let prior = [first];
+        let pubkey = Keypair::<E>::new(prior[0].delta_after, cs_hash, &prior, rng).public_key;
+        pubkey.verify_signature_of_knowledge(cs_hash, &prior).unwrap();
+
+        // the transcript must follow the prior contributions
+        pubkey.verify_signature_of_knowledge(cs_hash, &[]).unwrap_err();
+
+        let mut tampered = pubkey.clone();
+        tampered.s_delta = tampered.s_delta.mul(E::Fr::rand(rng));
+        tampered.transcript = hash_cs_pubkeys(cs_hash, &prior, tampered.s, tampered.s_delta, None);
+        let err = tampered.verify_signature_of_knowledge(cs_hash, &prior).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::VerificationError(VerificationError::InvalidRatio("Incorrect signature of knowledge"))
+        ));
+    }
+
+    #[test]
+    fn read_batch_too_many_contributions_fails() {
+        read_batch_too_many_contributions_fails_curve::<Bls12_377>()
+    }
+
+    fn read_batch_too_many_contributions_fails_curve<E: PairingEngine>() {
+        let mut header = vec![];
+        header.write_u32::<BigEndian>(u32::MAX).unwrap();
+        let err = PublicKey::<E>::read_batch(&mut &header[..]).unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::InvalidLength)));
+    }
+
+    #[test]
+    fn keypair_from_bytes() {
+        keypair_from_bytes_curve::<Bls12_377>()
+    }
+
+    fn keypair_from_bytes_curve<E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let delta_g1 = E::G1Affine::prime_subgroup_generator();
+        let cs_hash = [1; HASH_LEN];
+
+        let delta = E::Fr::rand(rng);
+        let mut delta_bytes = vec![];
+        delta.serialize(&mut delta_bytes).unwrap();
+        let keypair = Keypair::<E>::from_bytes(delta_g1, cs_hash, &[], &delta_bytes, rng).unwrap();
+        assert_eq!(keypair.private_key.delta, delta);
+        assert_eq!(keypair.public_key.delta_after, delta_g1.mul(delta));
+
+        // the public key is a valid first contribution
+        let contributions = [keypair.public_key.clone()];
+        let hashes = verify_transcript(cs_hash, &contributions).unwrap();
+        assert_eq!(hashes, vec![keypair.public_key.hash()]);
+
+        // out of range and truncated values are rejected
+        let out_of_range = vec![0xff; delta_bytes.len()];
+        assert!(Keypair::<E>::from_bytes(delta_g1, cs_hash, &[], &out_of_range, rng).is_err());
+        assert!(Keypair::<E>::from_bytes(delta_g1, cs_hash, &[], &delta_bytes[1..], rng).is_err());
+        delta_bytes.push(0);
+        assert!(Keypair::<E>::from_bytes(delta_g1, cs_hash, &[], &delta_bytes, rng).is_err());
+
+        let mut zero_bytes = vec![];
+        E::Fr::zero().serialize(&mut zero_bytes).unwrap();
+        let err = Keypair::<E>::from_bytes(delta_g1, cs_hash, &[], &zero_bytes, rng).unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::ZeroDelta)));
+    }
+
+    #[test]
+    fn zeroize_private_key() {
+        zeroize_private_key_curve::<Bls12_377>()
+    }
+
+    fn zeroize_private_key_curve<E: PairingEngine>() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<PrivateKey<E>>();
+        assert_zeroize_on_drop::<SecretScalar<E::Fr>>();
+
+        let delta_g1 = E::G1Affine::prime_subgroup_generator();
+        let mut keypair = Keypair::<E>::new(delta_g1, [0; HASH_LEN], &[], &mut thread_rng());
+        assert!(!keypair.private_key.delta.is_zero());
+        keypair.private_key.zeroize();
+        assert!(keypair.private_key.delta.is_zero());
+    }
 }
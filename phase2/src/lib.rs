@@ -10,6 +10,8 @@ mod polynomial;
 
 pub mod chunked_groth16;
 
+pub mod pow;
+
 cfg_if! {
     if #[cfg(feature = "wasm")] {
         use wasm_bindgen::prelude::*;
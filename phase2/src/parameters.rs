@@ -3,37 +3,83 @@ use cfg_if::cfg_if;
 cfg_if! {
     if #[cfg(not(feature = "wasm"))] {
         use super::polynomial::eval;
-        use snarkvm_fields::Zero;
-        use snarkvm_r1cs::SynthesisError;
+
+        use memmap::{MmapMut, MmapOptions};
+        use std::{
+            cmp::{max, min},
+            fs::OpenOptions,
+            path::Path,
+        };
     }
 }
 
-use super::keypair::{hash_cs_pubkeys, Keypair, PublicKey};
+use super::{
+    helpers::{ensure_unchanged_hash, read_exact_ctx},
+    keypair::{CryptoRngCore, Keypair, PublicKey},
+    pow::{ensure_pow_difficulty, solve_pow, verify_pow},
+};
 
 use setup_utils::*;
 
 use snarkvm_curves::{AffineCurve, PairingEngine};
-use snarkvm_fields::{Field, One};
-use snarkvm_r1cs::{ConstraintSynthesizer, ConstraintSystem, Index, Variable};
-use snarkvm_utilities::{CanonicalDeserialize, CanonicalSerialize};
+use snarkvm_fields::{Field, One, Zero};
+use snarkvm_r1cs::{ConstraintCounter, ConstraintSynthesizer, ConstraintSystem, Index, Variable};
+use snarkvm_utilities::{CanonicalDeserialize, CanonicalSerialize, ConstantSerializedSize};
 
-use rand::{CryptoRng, Rng};
+use rand::{CryptoRng, Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use snarkvm_algorithms::{
-    hash_to_curve::hash_to_curve,
-    snark::groth16::{KeypairAssembly, ProvingKey, VerifyingKey},
+    cfg_iter,
+    snark::groth16::{
+        create_random_proof,
+        prepare_verifying_key,
+        verify_proof,
+        KeypairAssembly,
+        PreparedVerifyingKey,
+        ProvingKey,
+        VerifyingKey,
+    },
 };
+#[cfg(feature = "unsafe-fast-qap")]
+use std::any::Any;
 use std::{
+    collections::HashSet,
+    convert::TryFrom,
     fmt,
-    io::{self, Read, Write},
+    io::{self, Read, Seek, SeekFrom, Write},
     ops::Mul,
 };
+use subtle::ConstantTimeEq;
+use tracing::info_span;
+
+/// The magic bytes which start serialized `MPCParameters`
+pub const PARAMS_MAGIC: [u8; 4] = *b"AMPC";
+
+/// The version of the serialized `MPCParameters` format, which follows the magic bytes.
+/// It must be bumped whenever the layout after the header changes. In version 1, each
+/// contribution is followed by its optional identity (see `PublicKey::write`).
+pub const PARAMS_FORMAT_VERSION: u8 = 1;
+
+/// The size of the header of serialized `MPCParameters`: the magic bytes, the format
+/// version and the identifier of the curve (see `curve_id`).
+pub const PARAMS_HEADER_SIZE: usize = 4 + 1 + 8;
 
 /// MPC parameters are just like snarkVM's `ProvingKey` except, when serialized,
-/// they contain a transcript of contributions at the end, which can be verified.
+/// they start with a header and contain a transcript of contributions at the end,
+/// which can be verified.
 #[derive(Clone)]
 pub struct MPCParameters<E: PairingEngine> {
     pub params: ProvingKey<E>,
-    pub cs_hash: [u8; 64],
+    /// The hash of the circuit's constraint system. Prefer `MPCParameters::cs_hash`.
+    pub cs_hash: [u8; HASH_LEN],
+    /// The digest of the phase 1 `Groth16Params` these parameters were created from.
+    /// It is advisory: it is not bound into the `cs_hash` nor the transcript, so it
+    /// tells which phase 1 output the parameters claim to come from without proving
+    /// it. `verify` still rejects contributions which change it. It is all zero for
+    /// parameters read with `read_legacy`.
+    pub phase1_digest: [u8; HASH_LEN],
     pub contributions: Vec<PublicKey<E>>,
 }
 
@@ -41,9 +87,10 @@ impl<E: PairingEngine> fmt::Debug for MPCParameters<E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "MPCParameters {{ proving_key: {:?}, cs_hash: {:?}, contributions: {:?}}}",
+            "MPCParameters {{ proving_key: {:?}, cs_hash: {:?}, phase1_digest: {:?}, contributions: {:?}}}",
             self.params,
             &self.cs_hash[..],
+            &self.phase1_digest[..],
             self.contributions
         )
     }
@@ -53,6 +100,7 @@ impl<E: PairingEngine + PartialEq> PartialEq for MPCParameters<E> {
     fn eq(&self, other: &MPCParameters<E>) -> bool {
         self.params == other.params
             && &self.cs_hash[..] == other.cs_hash.as_ref()
+            && &self.phase1_digest[..] == other.phase1_digest.as_ref()
             && self.contributions == other.contributions
     }
 }
@@ -86,6 +134,21 @@ impl<E: PairingEngine> MPCParameters<E> {
     /// The resulting parameters are unsafe to use until there are contributions (see `contribute()`).
     #[cfg(not(feature = "wasm"))]
     pub fn new(assembly: KeypairAssembly<E>, params: Groth16Params<E>) -> Result<MPCParameters<E>> {
+        // The QAP must fit in the coefficients created from phase 1. Like setup2's
+        // ceremony size, this is the larger of the number of constraints and the
+        // number of variables (which already includes the "one" input here)
+        let needed = max(
+            qap_num_constraints(&assembly),
+            assembly.num_public_variables + assembly.num_private_variables,
+        );
+        let got = min(
+            min(params.coeffs_g1.len(), params.coeffs_g2.len()),
+            min(params.alpha_coeffs_g1.len(), params.beta_coeffs_g1.len()),
+        );
+        if needed > got {
+            return Err(Phase2Error::PhaseSizeTooSmall { needed, got }.into());
+        }
+
         // Evaluate the QAP against the coefficients created from phase 1
         let (a_g1, b_g1, b_g2, gamma_abc_g1, l) = eval::<E>(
             // Lagrange coeffs for Tau, read in from Phase 1
@@ -103,11 +166,9 @@ impl<E: PairingEngine> MPCParameters<E> {
 
         // Reject unconstrained elements, so that
         // the L query is always fully dense.
-        for e in l.iter() {
-            if e.is_zero() {
-                return Err(SynthesisError::UnconstrainedVariable.into());
-            }
-        }
+        check_dense_l::<E>(&l)?;
+
+        let phase1_digest = params.digest()?;
 
         let vk = VerifyingKey {
             alpha_g1: params.alpha_g1,
@@ -133,6 +194,7 @@ impl<E: PairingEngine> MPCParameters<E> {
         Ok(MPCParameters {
             params,
             cs_hash,
+            phase1_digest,
             contributions: vec![],
         })
     }
@@ -142,6 +204,55 @@ impl<E: PairingEngine> MPCParameters<E> {
         &self.params
     }
 
+    /// Consumes the parameters and returns the underlying Groth16 `ProvingKey`,
+    /// dropping the transcript once it is no longer needed.
+    pub fn into_proving_key(self) -> ProvingKey<E> {
+        self.params
+    }
+
+    /// Returns the hashes of the contributions which these parameters claim
+    /// to contain, in order.
+    ///
+    /// This does NOT verify the transcript, it only lists the hashes of the
+    /// public keys. Use `MPCParameters::verify` to check the contributions.
+    pub fn contribution_hashes(&self) -> Vec<[u8; HASH_LEN]> {
+        self.contributions.iter().map(|pubkey| pubkey.hash()).collect()
+    }
+
+    /// Get the hash of the constraint system these parameters are for. Comparing
+    /// it with the expected one is the cheapest way to reject parameters for the
+    /// wrong circuit before calling `verify`.
+    pub fn cs_hash(&self) -> &[u8; HASH_LEN] {
+        &self.cs_hash
+    }
+
+    /// Checks that these parameters are for the circuit identified by
+    /// the `expected` constraint system hash
+    pub fn check_cs_hash(&self, expected: &[u8; HASH_LEN]) -> Result<()> {
+        ensure_unchanged_hash(expected, &self.cs_hash, InvariantKind::CsHash)
+    }
+
+    /// Checks that the stored `cs_hash` is the hash of the proving key, e.g. after
+    /// the file was edited by hand. This is only possible before any contribution,
+    /// since contributions change the proving key but not the `cs_hash`. Contributed
+    /// parameters are checked by `verify` against the initial ones instead.
+    pub fn validate_cs_hash(&self) -> Result<()> {
+        if !self.contributions.is_empty() {
+            return Err(Phase2Error::CsHashNotRecomputable.into());
+        }
+        ensure_unchanged_hash(&hash_params(&self.params)?, &self.cs_hash, InvariantKind::CsHash)
+    }
+
+    /// Replaces the stored `cs_hash` by the hash of the proving key. Like
+    /// `validate_cs_hash`, this is only possible before any contribution.
+    pub fn recompute_cs_hash(&mut self) -> Result<()> {
+        if !self.contributions.is_empty() {
+            return Err(Phase2Error::CsHashNotRecomputable.into());
+        }
+        self.cs_hash = hash_params(&self.params)?;
+        Ok(())
+    }
+
     /// Contributes some randomness to the parameters. Only one
     /// contributor needs to be honest for the parameters to be
     /// secure.
@@ -151,17 +262,108 @@ impl<E: PairingEngine> MPCParameters<E> {
     /// sure their contribution is in the final parameters, by
     /// checking to see if it appears in the output of
     /// `MPCParameters::verify`.
-    pub fn contribute<R: Rng + CryptoRng>(&mut self, rng: &mut R) -> Result<[u8; 64]> {
+    ///
+    /// Parameters which only carry the query vectors (i.e. whose
+    /// `h` and `l` queries are both empty) cannot be contributed to.
+    pub fn contribute<R: Rng + CryptoRng>(&mut self, rng: &mut R) -> Result<[u8; HASH_LEN]> {
+        self.contribute_with_backend(rng, &CpuBackend)
+    }
+
+    /// Same as `contribute`, but the contribution's public key records the
+    /// contributor's `identity`, e.g. their name. The identity is hashed into
+    /// the transcript, so changing it afterwards breaks verification.
+    pub fn contribute_with_identity<R: Rng + CryptoRng>(
+        &mut self,
+        identity: &[u8],
+        rng: &mut R,
+    ) -> Result<[u8; HASH_LEN]> {
+        ensure_contributable(self.params.h_query.len(), self.params.l_query.len())?;
+        let keypair = Keypair::new_with_identity(
+            self.params.delta_g1,
+            self.cs_hash,
+            &self.contributions,
+            identity.to_vec(),
+            rng,
+        )?;
+        self.contribute_keypair(keypair, &CpuBackend)
+    }
+
+    /// Same as `contribute`, but the delta is composed from several sources of
+    /// randomness: one delta is sampled from each of `rngs` and the contribution
+    /// uses their product, so it is unpredictable as long as one of the sources
+    /// is, e.g. when combining the OS RNG with a hardware RNG. Everything still
+    /// runs on this machine, which learns the composed delta like in any other
+    /// contribution, so this does not protect against a compromised machine.
+    /// The result is verified exactly like a regular contribution.
+    pub fn contribute_composed(&mut self, rngs: &mut [&mut dyn CryptoRngCore]) -> Result<[u8; HASH_LEN]> {
+        ensure_contributable(self.params.h_query.len(), self.params.l_query.len())?;
+        let keypair = Keypair::new_composed(self.params.delta_g1, self.cs_hash, &self.contributions, rngs)?;
+        self.contribute_keypair(keypair, &CpuBackend)
+    }
+
+    /// Same as `contribute`, but the randomness is derived from `seed`, so that
+    /// a contributor can later prove which seed their contribution came from.
+    /// The result is identical to calling `contribute` with a `ChaChaRng`
+    /// seeded with `seed`.
+    pub fn contribute_from_seed(&mut self, seed: &[u8; 32]) -> Result<[u8; HASH_LEN]> {
+        self.contribute(&mut ChaChaRng::from_seed(*seed))
+    }
+
+    /// Same as `contribute`, but returns a receipt of the contribution, which the
+    /// contributor can publish and anyone can check against the final parameters
+    /// with `ContributionReceipt::is_included_in`.
+    pub fn contribute_with_receipt<R: Rng + CryptoRng>(&mut self, rng: &mut R) -> Result<ContributionReceipt<E>> {
+        let hash = self.contribute(rng)?;
+        Ok(ContributionReceipt {
+            hash,
+            delta_after: self.params.delta_g1,
+            contribution_index: self.contributions.len() - 1,
+            cs_hash: self.cs_hash,
+        })
+    }
+
+    /// Contributes randomness derived from a public beacon, by iterating SHA-256
+    /// `iterations` times over `beacon_hash` and seeding the contribution with
+    /// the result. This is the recommended final step of a ceremony, since
+    /// anyone can recompute it from the beacon. It is otherwise a regular
+    /// contribution, and is verified by `verify` like any other.
+    pub fn contribute_beacon(&mut self, beacon_hash: [u8; 32], iterations: u64) -> Result<[u8; HASH_LEN]> {
+        let mut seed = beacon_hash;
+        for _ in 0..iterations {
+            seed.copy_from_slice(&Sha256::digest(&seed));
+        }
+        self.contribute_from_seed(&seed)
+    }
+
+    /// Same as `contribute`, but the `l` and `h` queries are
+    /// multiplied by the provided `backend`.
+    pub fn contribute_with_backend<R: Rng + CryptoRng, B: ScalarMulBackend<E::G1Affine> + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        backend: &B,
+    ) -> Result<[u8; HASH_LEN]> {
+        ensure_contributable(self.params.h_query.len(), self.params.l_query.len())?;
+
         // Generate a keypair
+        let keypair = Keypair::new(self.params.delta_g1, self.cs_hash, &self.contributions, rng);
+        self.contribute_keypair(keypair, backend)
+    }
+
+    fn contribute_keypair<B: ScalarMulBackend<E::G1Affine> + ?Sized>(
+        &mut self,
+        keypair: Keypair<E>,
+        backend: &B,
+    ) -> Result<[u8; HASH_LEN]> {
         let Keypair {
             public_key,
             private_key,
-        } = Keypair::new(self.params.delta_g1, self.cs_hash, &self.contributions, rng);
+        } = keypair;
 
         // Invert delta and multiply the query's `l` and `h` by it
-        let delta_inv = private_key.delta.inverse().expect("nonzero");
-        batch_mul(&mut self.params.l_query, &delta_inv)?;
-        batch_mul(&mut self.params.h_query, &delta_inv)?;
+        let delta_inv = private_key.delta_inv();
+        backend.batch_mul(&mut self.params.l_query, &delta_inv)?;
+        backend.batch_mul(&mut self.params.h_query, &delta_inv)?;
+        drop(delta_inv);
 
         // Multiply the `delta_g1` and `delta_g2` elements by the private key's delta
         self.params.vk.delta_g2 = self.params.vk.delta_g2.mul(private_key.delta);
@@ -174,14 +376,279 @@ impl<E: PairingEngine> MPCParameters<E> {
         Ok(public_key.hash())
     }
 
+    /// Same as `contribute`, but operates on the serialized parameters in the
+    /// file at `path` through a memory map, so that the `h` and `l` queries
+    /// never need to be fully loaded in memory. The file is left in the same
+    /// state as reading it, calling `contribute` and writing it back. If this
+    /// function fails, the file must be considered corrupted.
+    #[cfg(not(feature = "wasm"))]
+    pub fn contribute_mmap<R: Rng + CryptoRng>(path: &Path, rng: &mut R) -> Result<[u8; HASH_LEN]> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let mut map = unsafe { MmapOptions::new().map_mut(&file)? };
+
+        // Read the header and locate the H and L queries
+        let mut reader = io::Cursor::new(&map[..]);
+        read_header::<E, _>(&mut reader)?;
+        let mut vk = VerifyingKey::<E>::deserialize(&mut reader)?;
+        let beta_g1 = E::G1Affine::deserialize(&mut reader)?;
+        let delta_g1 = E::G1Affine::deserialize(&mut reader)?;
+        let header_len = reader.position() as usize;
+        let mut queries = vec![];
+        for &size in &[
+            E::G1Affine::SERIALIZED_SIZE,
+            E::G1Affine::SERIALIZED_SIZE,
+            E::G2Affine::SERIALIZED_SIZE,
+            E::G1Affine::SERIALIZED_SIZE,
+            E::G1Affine::SERIALIZED_SIZE,
+        ] {
+            let len = u64::deserialize(&mut reader)? as usize;
+            queries.push((reader.position() as usize, len));
+            reader.seek(SeekFrom::Current(query_byte_len(len, size)?))?;
+        }
+        let (h_start, h_len) = queries[3];
+        let (l_start, l_len) = queries[4];
+        let mut cs_hash = [0u8; HASH_LEN];
+        read_exact_ctx(&mut reader, &mut cs_hash, "cs_hash")?;
+        // the phase 1 digest is left unchanged
+        let mut phase1_digest = [0u8; HASH_LEN];
+        read_exact_ctx(&mut reader, &mut phase1_digest, "phase1_digest")?;
+        let contributions_start = reader.position();
+        let contributions = PublicKey::read_batch(&mut reader)?;
+        let contributions_end = reader.position();
+
+        ensure_contributable(h_len, l_len)?;
+
+        // Generate a keypair
+        let Keypair {
+            public_key,
+            private_key,
+        } = Keypair::new(delta_g1, cs_hash, &contributions, rng);
+
+        // Invert delta and multiply the query's `l` and `h` by it
+        let delta_inv = private_key.delta_inv();
+        mul_mmap_query::<E::G1Affine>(&mut map, l_start, l_len, &delta_inv)?;
+        mul_mmap_query::<E::G1Affine>(&mut map, h_start, h_len, &delta_inv)?;
+        drop(delta_inv);
+
+        // Multiply the `delta_g1` and `delta_g2` elements by the private key's delta
+        vk.delta_g2 = vk.delta_g2.mul(private_key.delta);
+        let delta_g1 = delta_g1.mul(private_key.delta);
+        // Ensure the private key is no longer used
+        drop(private_key);
+        {
+            let mut header = &mut map[PARAMS_HEADER_SIZE..header_len];
+            vk.serialize(&mut header)?;
+            beta_g1.serialize(&mut header)?;
+            delta_g1.serialize(&mut header)?;
+        }
+        map.flush()?;
+        drop(map);
+
+        // Append the public key to the contributions. It is written right after
+        // the last one rather than at the end of the file, and anything trailing
+        // the contributions is discarded, so that the output is the same as `write`
+        file.seek(SeekFrom::Start(contributions_start))?;
+        file.write_all(&(contributions.len() as u32 + 1).to_be_bytes())?;
+        file.seek(SeekFrom::Start(contributions_end))?;
+        public_key.write(&mut file)?;
+        let end = file.seek(SeekFrom::Current(0))?;
+        file.set_len(end)?;
+        file.flush()?;
+
+        // Return the pubkey's hash
+        Ok(public_key.hash())
+    }
+
+    /// Same as `contribute`, but also solves the coordinator's proof of work
+    /// challenge over the contribution hash. Returns the contribution hash
+    /// along with the proof of work solution.
+    pub fn contribute_with_pow<R: Rng + CryptoRng>(
+        &mut self,
+        rng: &mut R,
+        nonce: &[u8],
+        difficulty: u32,
+    ) -> Result<([u8; HASH_LEN], u64)> {
+        // Reject an impossible difficulty before contributing
+        ensure_pow_difficulty(difficulty)?;
+        let hash = self.contribute(rng)?;
+        let solution = solve_pow(&hash, nonce, difficulty)?;
+        Ok((hash, solution))
+    }
+
+    /// Scales `delta_g1` and `delta_g2` by a random scalar without updating the
+    /// `h` and `l` queries, for building parameters which `verify` must reject.
+    /// The last contribution's `delta_after` is scaled as well, so that the
+    /// failure is only caught by the query ratio checks.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn corrupt_delta<R: Rng>(&mut self, rng: &mut R) {
+        let scalar = <E::Fr as snarkvm_utilities::UniformRand>::rand(rng);
+        self.params.vk.delta_g2 = self.params.vk.delta_g2.mul(scalar);
+        self.params.delta_g1 = self.params.delta_g1.mul(scalar);
+        if let Some(pubkey) = self.contributions.last_mut() {
+            pubkey.delta_after = self.params.delta_g1;
+        }
+    }
+
+    /// Checks the proof of work of the last contribution. This is cheap,
+    /// so it should be done before calling `verify`.
+    pub fn verify_pow(&self, nonce: &[u8], difficulty: u32, solution: u64) -> Result<()> {
+        let pubkey = self.contributions.last().ok_or(Phase2Error::NoContributions)?;
+        verify_pow(&pubkey.hash(), nonce, difficulty, solution)
+    }
+
     /// Verify the correctness of the parameters, given a circuit
     /// instance. This will return all of the hashes that
     /// contributors obtained when they ran
     /// `MPCParameters::contribute`, for ensuring that contributions
     /// exist in the final parameters.
-    pub fn verify(&self, after: &Self) -> Result<Vec<[u8; 64]>> {
+    ///
+    /// The pairing checks of the transcript are batched into a single one, and
+    /// the random linear combinations used to batch them and the H and L ratio
+    /// checks are sampled from `thread_rng`, i.e. from fresh OS entropy.
+    pub fn verify(&self, after: &Self) -> Result<Vec<[u8; HASH_LEN]>> {
+        self.verify_with_rng(after, &mut rand::thread_rng())
+    }
+
+    /// Same as `verify`, but the random linear combinations used to batch the
+    /// H and L ratio checks and the pairing checks of the transcript are sampled
+    /// from `rng`. Seeding it makes the verification reproducible, e.g. for test
+    /// vectors.
+    pub fn verify_with_rng<R: Rng + CryptoRng>(&self, after: &Self, rng: &mut R) -> Result<Vec<[u8; HASH_LEN]>> {
+        let span = info_span!("verify");
+        let _enter = span.enter();
+
+        self.verify_params_with(after, rng, QueryCheck::Elements, &mut check_same_ratio::<E>)?;
+
+        // generate the transcript from the current contributions and the previous cs_hash
+        let span = info_span!("transcript");
+        let _enter = span.enter();
+        verify_transcript_suffix_batched(self.cs_hash, &after.contributions, 0, rng)
+    }
+
+    /// Checks that no two contributions have the same `delta_after`, i.e. that
+    /// no contribution was replayed. Returns the index of the first repeated one.
+    pub fn check_no_duplicate_contributions(&self) -> Result<()> {
+        let mut seen = HashSet::with_capacity(self.contributions.len());
+        for (index, pubkey) in self.contributions.iter().enumerate() {
+            let mut delta_after = vec![];
+            pubkey.delta_after.serialize(&mut delta_after)?;
+            if !seen.insert(delta_after) {
+                return Err(Phase2Error::DuplicateContribution { index }.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Lazily checks the transcript of these parameters, like `verify_transcript`,
+    /// yielding the hash of each contribution once its signature of knowledge and
+    /// delta were checked. Nothing is checked until the iterator is advanced, and
+    /// it ends after the first error.
+    pub fn verified_hashes_iter(&self) -> impl Iterator<Item = Result<[u8; HASH_LEN]>> + '_ {
+        self.contributions.iter().enumerate().scan(
+            Some(E::G1Affine::prime_subgroup_generator()),
+            move |old_delta, (i, pubkey)| {
+                let delta = (*old_delta)?;
+                let result = pubkey
+                    .signature_of_knowledge_point(self.cs_hash, &self.contributions[0..i])
+                    .and_then(|r| {
+                        // Check the change with the previous G1 Delta is consistent
+                        check_same_ratio::<E>(
+                            &(delta, pubkey.delta_after),
+                            &(r, pubkey.r_delta),
+                            "Inconsistent G1 Delta",
+                        )
+                    });
+                *old_delta = match result {
+                    Ok(()) => Some(pubkey.delta_after),
+                    Err(_) => None,
+                };
+                Some(result.map(|_| pubkey.hash()))
+            },
+        )
+    }
+
+    /// Verifies several independent (before, after) pairs at once, e.g. when a
+    /// coordinator runs multiple ceremonies in parallel. Returns the contribution
+    /// hashes of each pair, as `verify` would.
+    ///
+    /// All the non-pairing checks and the transcript hashing are still run for
+    /// each pair, but the pairing equations of every pair are checked together
+    /// with a single random linear combination, so that only one final
+    /// exponentiation is needed. This preserves soundness: each equation
+    /// `e(a, d) == e(b, c)` is scaled by an independent random scalar `r_i`, and
+    /// if any of them does not hold, the combined product is the identity with
+    /// probability at most `1/|Fr|`, since the coefficient of the failing equation
+    /// would have to cancel out all the others. If the batch fails, the equations
+    /// are checked one by one to report the first one which is invalid.
+    pub fn batch_verify(pairs: &[(&Self, &Self)]) -> Result<Vec<Vec<[u8; HASH_LEN]>>> {
+        let rng = &mut rand::thread_rng();
+        let mut equations = vec![];
+        let mut labels = vec![];
+        let mut hashes = Vec::with_capacity(pairs.len());
+        for (before, after) in pairs {
+            before.verify_params_with(after, rng, QueryCheck::Elements, &mut |g1, g2, err| {
+                equations.push((*g1, *g2));
+                labels.push(err);
+                Ok(())
+            })?;
+
+            let mut old_delta = E::G1Affine::prime_subgroup_generator();
+            for (i, pubkey) in after.contributions.iter().enumerate() {
+                let r = pubkey.transcript_point(before.cs_hash, &after.contributions[0..i])?;
+                equations.push(((pubkey.s, pubkey.s_delta), (r, pubkey.r_delta)));
+                labels.push("Incorrect signature of knowledge");
+                equations.push(((old_delta, pubkey.delta_after), (r, pubkey.r_delta)));
+                labels.push("Inconsistent G1 Delta");
+                old_delta = pubkey.delta_after;
+            }
+            hashes.push(after.contribution_hashes());
+        }
+
+        let results = batch_same_ratio::<E>(&equations)?;
+        if let Some(i) = results.iter().position(|valid| !valid) {
+            return Err(VerificationError::InvalidRatio(labels[i]).into());
+        }
+
+        Ok(hashes)
+    }
+
+    /// Same as `verify`, but also returns for each contribution the pairing
+    /// value which was computed by its delta ratio check, so that a coordinator
+    /// can log it and a third party can independently recompute it.
+    ///
+    /// Unlike `verify`, the pairing checks of the transcript are not batched,
+    /// since the pairing of each delta check is needed, so this is slower for
+    /// long ceremonies.
+    pub fn verify_with_audit(&self, after: &Self) -> Result<Vec<ContributionAudit<E>>> {
+        let span = info_span!("verify");
+        let _enter = span.enter();
+
+        self.verify_params_with(
+            after,
+            &mut rand::thread_rng(),
+            QueryCheck::Elements,
+            &mut check_same_ratio::<E>,
+        )?;
+
+        // generate the transcript from the current contributions and the previous cs_hash
+        let span = info_span!("transcript");
+        let _enter = span.enter();
+        verify_transcript_with_audit(self.cs_hash, &after.contributions)
+    }
+
+    /// Runs all the checks of `verify` except for the transcript, passing each
+    /// pairing ratio to `check` instead of checking it directly
+    fn verify_params_with<R, F>(&self, after: &Self, rng: &mut R, queries: QueryCheck, check: &mut F) -> Result<()>
+    where
+        R: Rng + CryptoRng,
+        F: FnMut(&(E::G1Affine, E::G1Affine), &(E::G2Affine, E::G2Affine), &'static str) -> Result<()>,
+    {
         let before = self;
+        // Report replayed contributions before any of the checks they would fail
+        after.check_no_duplicate_contributions()?;
 
+        let span = info_span!("delta");
+        let enter = span.enter();
         let pubkey = if let Some(pubkey) = after.contributions.last() {
             pubkey
         } else {
@@ -191,11 +658,12 @@ impl<E: PairingEngine> MPCParameters<E> {
         // Current parameters should have consistent delta in G1
         ensure_unchanged(pubkey.delta_after, after.params.delta_g1, InvariantKind::DeltaG1)?;
         // Current parameters should have consistent delta in G2
-        check_same_ratio::<E>(
+        check(
             &(E::G1Affine::prime_subgroup_generator(), pubkey.delta_after),
             &(E::G2Affine::prime_subgroup_generator(), after.params.vk.delta_g2),
             "Inconsistent G2 Delta",
         )?;
+        drop(enter);
 
         // None of the previous transformations should change
         ensure_unchanged(
@@ -205,12 +673,23 @@ impl<E: PairingEngine> MPCParameters<E> {
         )?;
 
         // cs_hash should be the same
-        ensure_unchanged(&before.cs_hash[..], &after.cs_hash[..], InvariantKind::CsHash)?;
+        ensure_unchanged_hash(&before.cs_hash, &after.cs_hash, InvariantKind::CsHash)?;
+        // and so should the phase 1 output the parameters were created from
+        ensure_unchanged_hash(&before.phase1_digest, &after.phase1_digest, InvariantKind::Phase1Digest)?;
 
         // H/L will change, but should have same length
         ensure_same_length(&before.params.h_query, &after.params.h_query)?;
         ensure_same_length(&before.params.l_query, &after.params.l_query)?;
 
+        // H/L are only ever multiplied by a nonzero delta, so they must remain fully dense
+        let span = info_span!("query_density");
+        let enter = span.enter();
+        ensure_dense(&after.params.h_query)?;
+        ensure_dense(&after.params.l_query)?;
+        drop(enter);
+
+        let span = info_span!("verifying_key");
+        let enter = span.enter();
         // A/B_G1/B_G2/Gamma G1/G2 doesn't change at all
         ensure_unchanged(
             before.params.vk.alpha_g1,
@@ -219,6 +698,12 @@ impl<E: PairingEngine> MPCParameters<E> {
         )?;
         ensure_unchanged(before.params.beta_g1, after.params.beta_g1, InvariantKind::BetaG1)?;
         ensure_unchanged(before.params.vk.beta_g2, after.params.vk.beta_g2, InvariantKind::BetaG2)?;
+        // Beta in G1 and G2 should correspond to the same scalar
+        check(
+            &(E::G1Affine::prime_subgroup_generator(), after.params.beta_g1),
+            &(E::G2Affine::prime_subgroup_generator(), after.params.vk.beta_g2),
+            "Inconsistent Beta",
+        )?;
         ensure_unchanged(
             before.params.vk.gamma_g2,
             after.params.vk.gamma_g2,
@@ -229,8 +714,11 @@ impl<E: PairingEngine> MPCParameters<E> {
             &after.params.vk.gamma_abc_g1,
             &InvariantKind::GammaAbcG1,
         )?;
+        drop(enter);
 
         // === Query related consistency checks ===
+        let span = info_span!("query_equality");
+        let enter = span.enter();
 
         // First 3 queries must be left untouched
         // TODO: Is it absolutely necessary to pass these potentially
@@ -239,150 +727,1336 @@ impl<E: PairingEngine> MPCParameters<E> {
         // the Powers of Tau from Phase 1, so we could defer construction of the
         // full parameters to the coordinator after all contributions have been
         // collected.
-        ensure_unchanged_vec(
-            &before.params.a_query,
-            &after.params.a_query,
-            &InvariantKind::AlphaG1Query,
+        match queries {
+            QueryCheck::Elements => {
+                ensure_unchanged_vec(
+                    &before.params.a_query,
+                    &after.params.a_query,
+                    &InvariantKind::AlphaG1Query,
+                )?;
+
+                ensure_unchanged_vec(
+                    &before.params.b_g1_query,
+                    &after.params.b_g1_query,
+                    &InvariantKind::BetaG1Query,
+                )?;
+
+                ensure_unchanged_vec(
+                    &before.params.b_g2_query,
+                    &after.params.b_g2_query,
+                    &InvariantKind::BetaG2Query,
+                )?;
+            }
+            QueryCheck::Digest(expected) => {
+                let got = after.queries_digest()?;
+                if !bool::from(expected[..].ct_eq(&got[..])) {
+                    return Err(Phase2Error::QueriesDigestMismatch {
+                        expected: hex::encode(&expected[..]),
+                        got: hex::encode(&got[..]),
+                    }
+                    .into());
+                }
+            }
+        }
+        drop(enter);
+
+        // H and L queries should be updated with delta^-1
+        let span = info_span!("query_ratios");
+        let _enter = span.enter();
+        check(
+            &merge_pairs_with_rng(&before.params.h_query, &after.params.h_query, rng),
+            &(after.params.vk.delta_g2, before.params.vk.delta_g2), // reversed for inverse
+            "H_query ratio check failed",
         )?;
 
-        ensure_unchanged_vec(
-            &before.params.b_g1_query,
-            &after.params.b_g1_query,
-            &InvariantKind::BetaG1Query,
+        check(
+            &merge_pairs_with_rng(&before.params.l_query, &after.params.l_query, rng),
+            &(after.params.vk.delta_g2, before.params.vk.delta_g2), // reversed for inverse
+            "L_query ratio check failed",
         )?;
 
-        ensure_unchanged_vec(
-            &before.params.b_g2_query,
-            &after.params.b_g2_query,
-            &InvariantKind::BetaG2Query,
+        Ok(())
+    }
+
+    /// Same as `verify`, but the A and B queries, which never change, are only
+    /// checked by comparing the `queries_digest` of `after` to `queries_digest`.
+    /// A coordinator which computed the digest of the canonical queries once can
+    /// then discard them: the queries of `self` are not read, so they may be empty.
+    pub fn verify_deltas_only(&self, after: &Self, queries_digest: &[u8; HASH_LEN]) -> Result<Vec<[u8; HASH_LEN]>> {
+        let span = info_span!("verify");
+        let _enter = span.enter();
+
+        let rng = &mut rand::thread_rng();
+        self.verify_params_with(
+            after,
+            rng,
+            QueryCheck::Digest(*queries_digest),
+            &mut check_same_ratio::<E>,
         )?;
 
-        // H and L queries should be updated with delta^-1
+        let span = info_span!("transcript");
+        let _enter = span.enter();
+        verify_transcript(self.cs_hash, &after.contributions)
+    }
+
+    /// Returns the BLAKE2b hash of the A, B_G1 and B_G2 queries, which are only
+    /// determined by the circuit and the phase 1 parameters.
+    pub fn queries_digest(&self) -> Result<[u8; HASH_LEN]> {
+        let sink = io::sink();
+        let mut sink = HashWriter::new(sink);
+        self.params.a_query.serialize(&mut sink)?;
+        self.params.b_g1_query.serialize(&mut sink)?;
+        self.params.b_g2_query.serialize(&mut sink)?;
+        Ok(sink.into_digest())
+    }
+
+    /// Checks that `after` only updated the delta of these parameters correctly,
+    /// i.e. that delta in G1 and G2 match the last contribution and that the
+    /// `h` and `l` queries were updated accordingly. Unlike `verify`, the
+    /// transcript and the signatures of knowledge are not checked, so this is
+    /// only sufficient when the transcript has already been verified
+    /// incrementally. A full `verify` is still required for a cold audit.
+    pub fn verify_delta_update(&self, after: &Self) -> Result<()> {
+        let before = self;
+
+        let pubkey = after.contributions.last().ok_or(Phase2Error::NoContributions)?;
+        ensure_unchanged(pubkey.delta_after, after.params.delta_g1, InvariantKind::DeltaG1)?;
+        check_same_ratio::<E>(
+            &(E::G1Affine::prime_subgroup_generator(), after.params.delta_g1),
+            &(E::G2Affine::prime_subgroup_generator(), after.params.vk.delta_g2),
+            "Inconsistent G2 Delta",
+        )?;
+        check_same_ratio::<E>(
+            &(before.params.delta_g1, after.params.delta_g1),
+            &(before.params.vk.delta_g2, after.params.vk.delta_g2),
+            "Inconsistent G1 Delta",
+        )?;
+
+        ensure_same_length(&before.params.h_query, &after.params.h_query)?;
+        ensure_same_length(&before.params.l_query, &after.params.l_query)?;
+        ensure_dense(&after.params.h_query)?;
+        ensure_dense(&after.params.l_query)?;
+
         check_same_ratio::<E>(
             &merge_pairs(&before.params.h_query, &after.params.h_query),
             &(after.params.vk.delta_g2, before.params.vk.delta_g2), // reversed for inverse
             "H_query ratio check failed",
         )?;
-
         check_same_ratio::<E>(
             &merge_pairs(&before.params.l_query, &after.params.l_query),
             &(after.params.vk.delta_g2, before.params.vk.delta_g2), // reversed for inverse
             "L_query ratio check failed",
-        )?;
+        )
+    }
 
-        // generate the transcript from the current contributions and the previous cs_hash
-        verify_transcript(before.cs_hash, &after.contributions)
+    /// Lists every part of the parameters which differs between these parameters and
+    /// `other`, instead of stopping at the first one like `verify`. `other` is expected
+    /// to extend the contributions of these parameters, so `InvariantKind::Contributions`
+    /// is listed if it does not. Note that the deltas change with every contribution, and
+    /// that the H and L queries are only compared by length.
+    pub fn diff(&self, other: &Self) -> Vec<InvariantKind> {
+        let (before, after) = (&self.params, &other.params);
+        let mut changed = vec![];
+        let mut check = |differs: bool, kind: InvariantKind| {
+            if differs {
+                changed.push(kind);
+            }
+        };
+
+        check(before.vk.alpha_g1 != after.vk.alpha_g1, InvariantKind::AlphaG1);
+        check(before.beta_g1 != after.beta_g1, InvariantKind::BetaG1);
+        check(before.vk.beta_g2 != after.vk.beta_g2, InvariantKind::BetaG2);
+        check(before.vk.gamma_g2 != after.vk.gamma_g2, InvariantKind::GammaG2);
+        check(
+            before.vk.gamma_abc_g1 != after.vk.gamma_abc_g1,
+            InvariantKind::GammaAbcG1,
+        );
+        check(before.delta_g1 != after.delta_g1, InvariantKind::DeltaG1);
+        check(before.vk.delta_g2 != after.vk.delta_g2, InvariantKind::DeltaG2);
+        check(before.a_query != after.a_query, InvariantKind::AlphaG1Query);
+        check(before.b_g1_query != after.b_g1_query, InvariantKind::BetaG1Query);
+        check(before.b_g2_query != after.b_g2_query, InvariantKind::BetaG2Query);
+        check(before.h_query.len() != after.h_query.len(), InvariantKind::HQuery);
+        check(before.l_query.len() != after.l_query.len(), InvariantKind::LQuery);
+        check(self.cs_hash[..] != other.cs_hash[..], InvariantKind::CsHash);
+        check(
+            self.phase1_digest[..] != other.phase1_digest[..],
+            InvariantKind::Phase1Digest,
+        );
+        check(
+            !other.contributions.starts_with(&self.contributions),
+            InvariantKind::Contributions,
+        );
+
+        changed
+    }
+
+    /// Checks that the verifying key of these parameters is a legitimate evolution of the
+    /// verifying key of the `initial` parameters: everything but delta in G2 must be unchanged,
+    /// and delta in G2 must be consistent with delta in G1. This is much cheaper than
+    /// verifying the full transcript.
+    pub fn verify_vk_consistency(&self, initial: &MPCParameters<E>) -> Result<()> {
+        let vk = &self.params.vk;
+        let initial_vk = &initial.params.vk;
+        ensure_unchanged(initial_vk.alpha_g1, vk.alpha_g1, InvariantKind::AlphaG1)?;
+        ensure_unchanged(initial_vk.beta_g2, vk.beta_g2, InvariantKind::BetaG2)?;
+        ensure_unchanged(initial_vk.gamma_g2, vk.gamma_g2, InvariantKind::GammaG2)?;
+        ensure_unchanged_vec(&initial_vk.gamma_abc_g1, &vk.gamma_abc_g1, &InvariantKind::GammaAbcG1)?;
+
+        check_same_ratio::<E>(
+            &(E::G1Affine::prime_subgroup_generator(), self.params.delta_g1),
+            &(E::G2Affine::prime_subgroup_generator(), vk.delta_g2),
+            "Inconsistent G2 Delta",
+        )
+    }
+
+    /// Same as `verify`, but `appended` must contain exactly one more
+    /// contribution than these parameters, as required when the chain
+    /// is stored as an append-only log. Returns the new contribution's hash.
+    pub fn verify_append(&self, appended: &Self) -> Result<[u8; HASH_LEN]> {
+        let expected = self.contributions.len() + 1;
+        let found = appended.contributions.len();
+        if found != expected {
+            return Err(Phase2Error::UnexpectedContributionCount { expected, found }.into());
+        }
+
+        let hashes = self.verify(appended)?;
+        Ok(hashes[hashes.len() - 1])
+    }
+
+    /// Verifies these parameters from scratch, given only the circuit and the
+    /// Groth16 parameters processed from Phase 1. The initial parameters are
+    /// reconstructed with `new`, and then checked against these parameters as
+    /// in `verify`. Returns the hashes of all the contributions.
+    #[cfg(not(feature = "wasm"))]
+    pub fn verify_against_circuit<C: ConstraintSynthesizer<E::Fr>>(
+        &self,
+        circuit: C,
+        params: Groth16Params<E>,
+    ) -> Result<Vec<[u8; HASH_LEN]>> {
+        let assembly = circuit_to_qap::<E, E, C>(circuit)?;
+        let initial = MPCParameters::new(assembly, params)?;
+        initial.verify(self)
+    }
+
+    /// Verifies a full sequence of parameters, where each step must contain
+    /// exactly one more contribution than the previous one. Returns the
+    /// hashes of all the contributions of the last step, or an error with the
+    /// index of the first step which is not a valid contribution.
+    pub fn verify_chain(steps: &[MPCParameters<E>]) -> Result<Vec<[u8; HASH_LEN]>> {
+        if steps.len() < 2 {
+            return Err(Phase2Error::NoContributions.into());
+        }
+
+        // the contributions of the first step are carried over by each step
+        let mut hashes = steps[0]
+            .contributions
+            .iter()
+            .map(|pubkey| pubkey.hash())
+            .collect::<Vec<_>>();
+        for (i, pair) in steps.windows(2).enumerate() {
+            let hash = pair[0]
+                .verify_append(&pair[1])
+                .map_err(|cause| Phase2Error::BrokenChain {
+                    index: i + 1,
+                    cause: Box::new(cause),
+                })?;
+            hashes.push(hash);
+        }
+
+        Ok(hashes)
+    }
+
+    /// Exports the contributions of the transcript as a JSON array of
+    /// `TranscriptEntry`, so that third parties can cross-check the hashes
+    /// without parsing the binary format. Only the contributions are used.
+    pub fn export_transcript_json(&self) -> String {
+        let entries = self
+            .contributions
+            .iter()
+            .enumerate()
+            .map(|(index, pubkey)| {
+                let mut delta_after = vec![];
+                pubkey
+                    .delta_after
+                    .serialize(&mut delta_after)
+                    .expect("serializing into a vector cannot fail");
+                TranscriptEntry {
+                    index,
+                    hash: hex::encode(&pubkey.hash()[..]),
+                    delta_after: hex::encode(delta_after),
+                }
+            })
+            .collect::<Vec<_>>();
+        serde_json::to_string(&entries).expect("serializing the transcript cannot fail")
+    }
+
+    /// Builds a publishable record of the ceremony from the hashes returned by
+    /// `verify`. This does not verify the contributions by itself, callers must
+    /// only produce an attestation for a transcript which they have verified, but
+    /// the hashes are checked against the ones of these parameters' transcript,
+    /// so that hashes returned for other parameters are rejected.
+    pub fn attestation(&self, verification: &[[u8; HASH_LEN]]) -> Result<CeremonyAttestation> {
+        let expected = self.contributions.len();
+        let found = verification.len();
+        if found != expected {
+            return Err(Phase2Error::UnexpectedContributionCount { expected, found }.into());
+        }
+        for (index, (pubkey, hash)) in self.contributions.iter().zip(verification).enumerate() {
+            if !bool::from(pubkey.hash()[..].ct_eq(&hash[..])) {
+                return Err(Phase2Error::BrokenInvariantAt {
+                    kind: InvariantKind::Transcript,
+                    index,
+                }
+                .into());
+            }
+        }
+
+        let mut vk = vec![];
+        self.params.vk.serialize(&mut vk)?;
+        let mut delta = vec![];
+        self.params.delta_g1.serialize(&mut delta)?;
+        self.params.vk.delta_g2.serialize(&mut delta)?;
+
+        Ok(CeremonyAttestation {
+            cs_hash: hex::encode(&self.cs_hash[..]),
+            vk_hash: hex::encode(calculate_hash(&vk)),
+            num_contributions: verification.len(),
+            contributions: verification.iter().map(|hash| hex::encode(&hash[..])).collect(),
+            delta_commitment: hex::encode(calculate_hash(&delta)),
+        })
     }
 
-    /// Serialize these parameters. The serialized parameters
-    /// can be read by snarkVM's Groth16 `ProvingKey`.
+    /// Serialize these parameters. The serialized parameters start with
+    /// a header (see `PARAMS_MAGIC`), followed by snarkVM's Groth16
+    /// `ProvingKey`, the `cs_hash`, the phase 1 digest and the contributions.
+    /// Because of the header, unlike the output of earlier versions, they
+    /// cannot be read by snarkVM's `ProvingKey::deserialize`: write the key
+    /// with `write_proving_key_only` for that. Files in the earlier format are
+    /// read with `read_legacy`.
     pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_header::<E, _>(writer)?;
         self.params.serialize(writer)?;
         writer.write_all(&self.cs_hash)?;
+        writer.write_all(&self.phase1_digest)?;
         PublicKey::write_batch(writer, &self.contributions)?;
 
         Ok(())
     }
 
-    /// Deserialize these parameters.
-    pub fn read<R: Read>(mut reader: R) -> Result<MPCParameters<E>> {
+    /// Same as `write`, but lets the caller choose how the points of the proving
+    /// key are encoded. `UseCompression::Yes` produces the same output as `write`
+    /// and roughly halves the size of the file, which is preferable when it is
+    /// sent over the network. `UseCompression::No` produces a larger file which is
+    /// faster to read back, since the points do not need to be decompressed.
+    /// The contributions are always uncompressed. Read it back with `read_compressed`.
+    pub fn write_compressed<W: Write>(&self, writer: &mut W, compression: UseCompression) -> Result<()> {
+        write_header::<E, _>(writer)?;
+        match compression {
+            UseCompression::Yes => self.params.serialize(writer)?,
+            UseCompression::No => self.params.serialize_uncompressed(writer)?,
+        }
+        writer.write_all(&self.cs_hash)?;
+        writer.write_all(&self.phase1_digest)?;
+        PublicKey::write_batch(writer, &self.contributions)?;
+
+        Ok(())
+    }
+
+    /// Deserialize parameters written before the header and the phase 1 digest were
+    /// introduced, i.e. a `ProvingKey` directly followed by the `cs_hash` and the
+    /// contributions, none of which could have an identity. The phase 1 digest was not
+    /// recorded, so it is left all zero. Writing the parameters back with `write`
+    /// migrates them to the current format.
+    pub fn read_legacy<R: Read>(mut reader: R) -> Result<MPCParameters<E>> {
         let params = ProvingKey::deserialize(&mut reader)?;
+        ensure_query_lengths(&params)?;
 
-        let mut cs_hash = [0u8; 64];
-        reader.read_exact(&mut cs_hash)?;
+        let mut cs_hash = [0u8; HASH_LEN];
+        read_exact_ctx(&mut reader, &mut cs_hash, "cs_hash")?;
 
-        let contributions = PublicKey::read_batch(&mut reader)?;
+        let contributions = PublicKey::read_batch_legacy(&mut reader)?;
 
         Ok(MPCParameters {
             params,
             cs_hash,
+            phase1_digest: [0u8; HASH_LEN],
             contributions,
         })
     }
-}
 
-/// This is a cheap helper utility that exists purely
-/// because Rust still doesn't have type-level integers
-/// and so doesn't implement `PartialEq` for `[T; 64]`
-pub fn contains_contribution(contributions: &[[u8; 64]], my_contribution: &[u8; 64]) -> bool {
-    for contrib in contributions {
-        if &contrib[..] == my_contribution.as_ref() {
-            return true;
-        }
-    }
+    /// Deserialize parameters written by `write_compressed` with the same `compression`.
+    pub fn read_compressed<R: Read>(mut reader: R, compression: UseCompression) -> Result<MPCParameters<E>> {
+        read_header::<E, _>(&mut reader)?;
+        let params = match compression {
+            UseCompression::Yes => ProvingKey::deserialize(&mut reader)?,
+            UseCompression::No => ProvingKey::deserialize_uncompressed(&mut reader)?,
+        };
+        ensure_query_lengths(&params)?;
 
-    false
-}
+        let mut cs_hash = [0u8; HASH_LEN];
+        read_exact_ctx(&mut reader, &mut cs_hash, "cs_hash")?;
+        let mut phase1_digest = [0u8; HASH_LEN];
+        read_exact_ctx(&mut reader, &mut phase1_digest, "phase1_digest")?;
 
-// Helpers for invariant checking
-pub fn ensure_same_length<T, U>(a: &[T], b: &[U]) -> Result<()> {
-    if a.len() != b.len() {
-        return Err(Phase2Error::InvalidLength.into());
+        let contributions = PublicKey::read_batch(&mut reader)?;
+
+        Ok(MPCParameters {
+            params,
+            cs_hash,
+            phase1_digest,
+            contributions,
+        })
     }
-    Ok(())
-}
 
-pub fn ensure_unchanged_vec<T: PartialEq>(before: &[T], after: &[T], kind: &InvariantKind) -> Result<()> {
-    if before.len() != after.len() {
-        return Err(Phase2Error::InvalidLength.into());
+    /// Converts parameters written by `write_compressed` with the `from` compression
+    /// into the `to` compression, e.g. to produce a file which is faster to read back.
+    /// Every point of the proving key is checked to be in the prime order subgroup
+    /// on the way, so the output is only written for well-formed parameters.
+    pub fn recompress<R: Read, W: Write>(
+        reader: R,
+        writer: &mut W,
+        from: UseCompression,
+        to: UseCompression,
+    ) -> Result<()> {
+        let mpc = Self::read_compressed(reader, from)?;
+        mpc.check_subgroups()?;
+        mpc.write_compressed(writer, to)
     }
-    for (before, after) in before.iter().zip(after) {
-        // TODO: Make the error take a reference
-        ensure_unchanged(before, after, kind.clone())?
+
+    /// Checks that all the points of the proving key are in the prime order subgroup
+    fn check_subgroups(&self) -> Result<()> {
+        let params = &self.params;
+        check_subgroup(&[params.vk.alpha_g1, params.beta_g1, params.delta_g1])?;
+        check_subgroup(&[params.vk.beta_g2, params.vk.gamma_g2, params.vk.delta_g2])?;
+        check_subgroup(&params.vk.gamma_abc_g1)?;
+        check_subgroup(&params.a_query)?;
+        check_subgroup(&params.b_g1_query)?;
+        check_subgroup(&params.b_g2_query)?;
+        check_subgroup(&params.h_query)?;
+        check_subgroup(&params.l_query)
     }
-    Ok(())
-}
 
-pub fn ensure_unchanged<T: PartialEq>(before: T, after: T, kind: InvariantKind) -> Result<()> {
-    if before != after {
-        return Err(Phase2Error::BrokenInvariant(kind).into());
+    /// Returns the number of bytes `write_compressed` writes for these parameters
+    /// with the provided `compression`. `UseCompression::Yes` is the size of `write`.
+    pub fn serialized_size(&self, compression: UseCompression) -> usize {
+        let params_size = match compression {
+            UseCompression::Yes => self.params.serialized_size(),
+            UseCompression::No => self.params.uncompressed_size(),
+        };
+        PARAMS_HEADER_SIZE
+            + params_size
+            + self.cs_hash.len()
+            + self.phase1_digest.len()
+            + PublicKey::batch_size(&self.contributions)
     }
-    Ok(())
-}
 
-pub fn verify_transcript<E: PairingEngine>(cs_hash: [u8; 64], contributions: &[PublicKey<E>]) -> Result<Vec<[u8; 64]>> {
-    let mut result = vec![];
-    let mut old_delta = E::G1Affine::prime_subgroup_generator();
-    for (i, pubkey) in contributions.iter().enumerate() {
-        let hash = hash_cs_pubkeys(cs_hash, &contributions[0..i], pubkey.s, pubkey.s_delta);
-        ensure_unchanged(&pubkey.transcript[..], &hash.as_ref()[..], InvariantKind::Transcript)?;
+    /// Same as `write`, followed by a BLAKE2b checksum of everything written,
+    /// so that corrupted transfers can be detected with `read_with_checksum`.
+    pub fn write_with_checksum<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let mut hash_writer = HashWriter::new(&mut *writer);
+        self.write(&mut hash_writer)?;
+        let checksum = hash_writer.into_hash();
+        writer.write_all(checksum.as_slice())?;
 
-        // generate the G2 point from the hash
-        let r = hash_to_curve::<E::G2Affine>(&hex::encode(hash.as_ref())).0;
+        Ok(())
+    }
 
-        // Check the signature of knowledge
-        check_same_ratio::<E>(
-            &(pubkey.s, pubkey.s_delta),
-            &(r, pubkey.r_delta),
-            "Incorrect signature of knowledge",
-        )?;
+    /// Serialize only the Groth16 `ProvingKey` of these parameters, without the
+    /// `cs_hash` and the contributions, so that it can be read directly with
+    /// snarkVM's `ProvingKey::deserialize`.
+    pub fn write_proving_key_only<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.params.serialize(writer)?;
+        Ok(())
+    }
 
-        // Check the change with the previous G1 Delta is consistent
-        check_same_ratio::<E>(
-            &(old_delta, pubkey.delta_after),
-            &(r, pubkey.r_delta),
-            "Inconsistent G1 Delta",
-        )?;
-        old_delta = pubkey.delta_after;
+    /// Serialize only the verifying key of these parameters, which is all that
+    /// is needed to verify proofs once the ceremony is over.
+    pub fn write_verifying_key<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.params.vk.serialize(writer)?;
+        Ok(())
+    }
 
-        result.push(pubkey.hash());
+    /// Deserialize a verifying key written by `write_verifying_key`.
+    pub fn read_verifying_key<R: Read>(mut reader: R) -> Result<VerifyingKey<E>> {
+        Ok(VerifyingKey::deserialize(&mut reader)?)
     }
 
-    Ok(result)
+    /// Prepares the verifying key of these parameters, so that proofs can be
+    /// checked with snarkVM's `verify_proof`.
+    pub fn prepared_verifying_key(&self) -> PreparedVerifyingKey<E> {
+        prepare_verifying_key(self.params.vk.clone())
+    }
+
+    /// Same as `write`, but each element of the query vectors is written
+    /// directly into `writer`. The output is identical to that of `write`.
+    pub fn write_streaming<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_header::<E, _>(writer)?;
+        self.params.vk.serialize(writer)?;
+        self.params.beta_g1.serialize(writer)?;
+        self.params.delta_g1.serialize(writer)?;
+        write_query(writer, &self.params.a_query)?;
+        write_query(writer, &self.params.b_g1_query)?;
+        write_query(writer, &self.params.b_g2_query)?;
+        write_query(writer, &self.params.h_query)?;
+        write_query(writer, &self.params.l_query)?;
+        writer.write_all(&self.cs_hash)?;
+        writer.write_all(&self.phase1_digest)?;
+        PublicKey::write_batch(writer, &self.contributions)?;
+
+        Ok(())
+    }
+
+    /// Deserialize these parameters.
+    pub fn read<R: Read>(reader: R) -> Result<MPCParameters<E>> {
+        Self::read_with_contributions_progress(reader, |_, _| ())
+    }
+
+    /// Same as `read`, for parameters without contributions, which additionally
+    /// checks that their `cs_hash` matches the proving key (see `validate_cs_hash`).
+    pub fn read_and_validate_cs_hash<R: Read>(reader: R) -> Result<MPCParameters<E>> {
+        let mpc = Self::read(reader)?;
+        mpc.validate_cs_hash()?;
+        Ok(mpc)
+    }
+
+    /// Same as `read`, but calls `progress(contributions_done, contributions_total)`
+    /// after each contribution of the transcript has been read.
+    pub fn read_with_contributions_progress<R: Read, F: FnMut(usize, usize)>(
+        mut reader: R,
+        progress: F,
+    ) -> Result<MPCParameters<E>> {
+        read_header::<E, _>(&mut reader)?;
+        let params = ProvingKey::deserialize(&mut reader)?;
+        ensure_query_lengths(&params)?;
+
+        let mut cs_hash = [0u8; HASH_LEN];
+        read_exact_ctx(&mut reader, &mut cs_hash, "cs_hash")?;
+        let mut phase1_digest = [0u8; HASH_LEN];
+        read_exact_ctx(&mut reader, &mut phase1_digest, "phase1_digest")?;
+
+        let contributions = PublicKey::read_batch_with_progress(&mut reader, progress)?;
+
+        Ok(MPCParameters {
+            params,
+            cs_hash,
+            phase1_digest,
+            contributions,
+        })
+    }
+
+    /// Deserialize parameters written by `write_with_checksum`. The whole reader
+    /// is consumed and the checksum is checked before any point is parsed.
+    pub fn read_with_checksum<R: Read>(mut reader: R) -> Result<MPCParameters<E>> {
+        let mut buffer = vec![];
+        reader.read_to_end(&mut buffer)?;
+        if buffer.len() < 64 {
+            return Err(Error::InvalidLength {
+                expected: 64,
+                got: buffer.len(),
+            });
+        }
+
+        let (body, expected) = buffer.split_at(buffer.len() - 64);
+        let checksum = calculate_hash(body);
+        if checksum.as_slice() != expected {
+            return Err(Phase2Error::ChecksumMismatch {
+                expected: hex::encode(expected),
+                got: hex::encode(checksum),
+            }
+            .into());
+        }
+
+        Self::read(body)
+    }
+
+    /// Same as `read`, but from an async reader, e.g. a parameter upload received by
+    /// a tokio service. The whole reader is consumed asynchronously, then the points
+    /// are deserialized from memory. Deserializing, like `verify`, is CPU-bound and
+    /// synchronous, so for large parameters both are best run on a blocking pool
+    /// (e.g. with `tokio::task::spawn_blocking`).
+    ///
+    /// Since the reader is buffered in memory, it fails as soon as it yields more than
+    /// `max_len` bytes, e.g. the `serialized_size` of the expected parameters.
+    #[cfg(feature = "async")]
+    pub async fn read_async<R: tokio::io::AsyncRead + Unpin>(reader: R, max_len: usize) -> Result<MPCParameters<E>> {
+        use tokio::io::AsyncReadExt;
+
+        let mut buffer = vec![];
+        // read one more byte than allowed to tell whether the limit was exceeded
+        let limit = (max_len as u64).saturating_add(1);
+        reader.take(limit).read_to_end(&mut buffer).await?;
+        if buffer.len() > max_len {
+            return Err(Phase2Error::InputTooLarge { max_len }.into());
+        }
+        Self::read(&buffer[..])
+    }
+
+    /// Same as `read`, but also checks the query vectors as requested by `check`.
+    /// `CheckForCorrectness::OnlyNonZero` only rejects points at infinity in the
+    /// `h` and `l` queries, which is appropriate when reloading parameters from a
+    /// trusted source that have not been verified since.
+    pub fn read_checked<R: Read>(reader: R, check: CheckForCorrectness) -> Result<MPCParameters<E>> {
+        let mpc = Self::read(reader)?;
+        let params = &mpc.params;
+        if let CheckForCorrectness::Full | CheckForCorrectness::OnlyNonZero = check {
+            ensure_dense(&params.h_query)?;
+            ensure_dense(&params.l_query)?;
+        }
+        if let CheckForCorrectness::Full | CheckForCorrectness::OnlyInGroup = check {
+            check_subgroup(&params.a_query)?;
+            check_subgroup(&params.b_g1_query)?;
+            check_subgroup(&params.b_g2_query)?;
+            check_subgroup(&params.h_query)?;
+            check_subgroup(&params.l_query)?;
+        }
+
+        Ok(mpc)
+    }
+
+    /// Deserialize these parameters, checking that the SHA-256 digest of the
+    /// complete stream matches `expected`, e.g. when the parameters were fetched
+    /// from content-addressed storage. The whole reader is consumed.
+    pub fn read_with_expected_digest<R: Read>(reader: R, expected: [u8; 32]) -> Result<MPCParameters<E>> {
+        let mut reader = Sha256Reader {
+            reader,
+            hasher: Sha256::new(),
+        };
+        let params = Self::read(&mut reader)?;
+        // the digest must also cover any trailing bytes
+        io::copy(&mut reader, &mut io::sink())?;
+
+        let digest = reader.hasher.finalize();
+        if digest.as_slice() != expected {
+            return Err(Phase2Error::DigestMismatch {
+                expected: hex::encode(expected),
+                got: hex::encode(digest),
+            }
+            .into());
+        }
+
+        Ok(params)
+    }
+
+    /// Same as `read`, but checks the length prefix of each vector against the
+    /// `expected` dimensions before decoding it, so that parameters for another
+    /// circuit, or truncated or padded uploads, are rejected early with the name
+    /// of the offending vector.
+    pub fn read_with_dimensions<R: Read>(mut reader: R, expected: &ParamDimensions) -> Result<MPCParameters<E>> {
+        let num_private = expected.num_variables.saturating_sub(expected.num_public);
+
+        read_header::<E, _>(&mut reader)?;
+        let vk = VerifyingKey::<E>::deserialize(&mut reader)?;
+        let got = vk.gamma_abc_g1.len();
+        if got != expected.num_public {
+            return Err(Phase2Error::UnexpectedLength {
+                field: "gamma_abc_g1",
+                expected: expected.num_public,
+                got,
+            }
+            .into());
+        }
+        let beta_g1 = E::G1Affine::deserialize(&mut reader)?;
+        let delta_g1 = E::G1Affine::deserialize(&mut reader)?;
+
+        let a_query = read_query_of_len(&mut reader, "a_query", expected.num_variables)?;
+        let b_g1_query = read_query_of_len(&mut reader, "b_g1_query", expected.num_variables)?;
+        let b_g2_query = read_query_of_len(&mut reader, "b_g2_query", expected.num_variables)?;
+        let h_query = read_query_of_len(&mut reader, "h_query", expected.phase2_size.saturating_sub(1))?;
+        let l_query = read_query_of_len(&mut reader, "l_query", num_private)?;
+
+        let params = ProvingKey {
+            vk,
+            beta_g1,
+            delta_g1,
+            a_query,
+            b_g1_query,
+            b_g2_query,
+            h_query,
+            l_query,
+        };
+
+        let mut cs_hash = [0u8; HASH_LEN];
+        read_exact_ctx(&mut reader, &mut cs_hash, "cs_hash")?;
+        let mut phase1_digest = [0u8; HASH_LEN];
+        read_exact_ctx(&mut reader, &mut phase1_digest, "phase1_digest")?;
+
+        let contributions = PublicKey::read_batch(&mut reader)?;
+
+        Ok(MPCParameters {
+            params,
+            cs_hash,
+            phase1_digest,
+            contributions,
+        })
+    }
+
+    /// Deserialize these parameters, calling `progress(elements_done, elements_total)`
+    /// after each element of the query vectors has been decoded. The length prefixes
+    /// of all the query vectors are scanned before decoding, so the total is known
+    /// from the very first call.
+    pub fn read_with_progress<R: Read + Seek, F: FnMut(usize, usize)>(
+        mut reader: R,
+        mut progress: F,
+    ) -> Result<MPCParameters<E>> {
+        read_header::<E, _>(&mut reader)?;
+        let vk = VerifyingKey::<E>::deserialize(&mut reader)?;
+        let beta_g1 = E::G1Affine::deserialize(&mut reader)?;
+        let delta_g1 = E::G1Affine::deserialize(&mut reader)?;
+
+        // Scan the length prefixes of the A, B_G1, B_G2, H and L queries
+        let queries_start = reader.seek(SeekFrom::Current(0))?;
+        let mut total = 0;
+        for &size in &[
+            E::G1Affine::SERIALIZED_SIZE,
+            E::G1Affine::SERIALIZED_SIZE,
+            E::G2Affine::SERIALIZED_SIZE,
+            E::G1Affine::SERIALIZED_SIZE,
+            E::G1Affine::SERIALIZED_SIZE,
+        ] {
+            let len = u64::deserialize(&mut reader)? as usize;
+            reader.seek(SeekFrom::Current(query_byte_len(len, size)?))?;
+            total += len;
+        }
+        reader.seek(SeekFrom::Start(queries_start))?;
+
+        let mut done = 0;
+        let mut report = || {
+            done += 1;
+            progress(done, total);
+        };
+        let a_query = read_query_with_progress(&mut reader, &mut report)?;
+        let b_g1_query = read_query_with_progress(&mut reader, &mut report)?;
+        let b_g2_query = read_query_with_progress(&mut reader, &mut report)?;
+        let h_query = read_query_with_progress(&mut reader, &mut report)?;
+        let l_query = read_query_with_progress(&mut reader, &mut report)?;
+
+        let params = ProvingKey {
+            vk,
+            beta_g1,
+            delta_g1,
+            a_query,
+            b_g1_query,
+            b_g2_query,
+            h_query,
+            l_query,
+        };
+        ensure_query_lengths(&params)?;
+
+        let mut cs_hash = [0u8; HASH_LEN];
+        read_exact_ctx(&mut reader, &mut cs_hash, "cs_hash")?;
+        let mut phase1_digest = [0u8; HASH_LEN];
+        read_exact_ctx(&mut reader, &mut phase1_digest, "phase1_digest")?;
+
+        let contributions = PublicKey::read_batch(&mut reader)?;
+
+        Ok(MPCParameters {
+            params,
+            cs_hash,
+            phase1_digest,
+            contributions,
+        })
+    }
+}
+
+/// How `MPCParameters::verify_params_with` checks that the A and B queries are unchanged
+enum QueryCheck {
+    /// Compare the queries element by element, reporting the first changed one
+    Elements,
+    /// Compare the digest of the queries after the contribution to the expected one
+    Digest([u8; HASH_LEN]),
+}
+
+/// The dimensions of the circuit which determine the length of each vector of
+/// the parameters, with the same meaning as the arguments of `phase2_file_size`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParamDimensions {
+    pub num_public: usize,
+    pub num_variables: usize,
+    pub phase2_size: usize,
+}
+
+impl ParamDimensions {
+    /// Returns the dimensions of existing parameters
+    pub fn of<E: PairingEngine>(params: &ProvingKey<E>) -> Self {
+        ParamDimensions {
+            num_public: params.vk.gamma_abc_g1.len(),
+            num_variables: params.a_query.len(),
+            phase2_size: params.h_query.len() + 1,
+        }
+    }
+}
+
+/// Feeds all the bytes read from the inner reader to a SHA-256 hasher
+struct Sha256Reader<R> {
+    reader: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> Read for Sha256Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.reader.read(buf)?;
+        self.hasher.update(&buf[..len]);
+        Ok(len)
+    }
+}
+
+/// Reads a length-prefixed vector of elements, failing before any of them is
+/// decoded if its length is not `expected`
+fn read_query_of_len<C: AffineCurve, R: Read>(reader: &mut R, field: &'static str, expected: usize) -> Result<Vec<C>> {
+    let got = u64::deserialize(&mut *reader)? as usize;
+    if got != expected {
+        return Err(Phase2Error::UnexpectedLength { field, expected, got }.into());
+    }
+    let mut query = Vec::with_capacity(got);
+    for _ in 0..got {
+        query.push(C::deserialize(&mut *reader)?);
+    }
+    Ok(query)
+}
+
+/// Returns the number of bytes taken by `len` elements of `size` bytes, as an
+/// offset which can be seeked over. `len` is read from the input, so this fails
+/// instead of overflowing.
+fn query_byte_len(len: usize, size: usize) -> Result<i64> {
+    len.checked_mul(size)
+        .and_then(|bytes| i64::try_from(bytes).ok())
+        .ok_or_else(|| Phase2Error::SizeOverflow.into())
+}
+
+/// Reads a length-prefixed vector of elements, calling `report` after each one.
+/// The length is read from the input, so the vector grows as the elements are
+/// decoded instead of being allocated upfront.
+fn read_query_with_progress<C: AffineCurve, R: Read, F: FnMut()>(reader: &mut R, report: &mut F) -> Result<Vec<C>> {
+    let len = u64::deserialize(&mut *reader)? as usize;
+    let mut query = Vec::new();
+    for _ in 0..len {
+        query.push(C::deserialize(&mut *reader)?);
+        report();
+    }
+    Ok(query)
+}
+
+/// The number of elements multiplied at a time by `contribute_mmap`
+#[cfg(not(feature = "wasm"))]
+const MMAP_BATCH_SIZE: usize = 1 << 16;
+
+/// Multiplies the `len` serialized elements starting at `start` by `coeff`,
+/// in batches of `MMAP_BATCH_SIZE`, flushing each batch to disk
+#[cfg(not(feature = "wasm"))]
+fn mul_mmap_query<C: AffineCurve>(map: &mut MmapMut, start: usize, len: usize, coeff: &C::ScalarField) -> Result<()> {
+    let size = C::SERIALIZED_SIZE;
+    for batch_start in (0..len).step_by(MMAP_BATCH_SIZE) {
+        let batch_len = min(MMAP_BATCH_SIZE, len - batch_start);
+        let offset = start + batch_start * size;
+        let bytes = &mut map[offset..offset + batch_len * size];
+
+        let mut elements = Vec::with_capacity(batch_len);
+        for mut element_bytes in bytes.chunks(size) {
+            elements.push(C::deserialize(&mut element_bytes)?);
+        }
+        batch_mul(&mut elements, coeff)?;
+        for (mut element_bytes, element) in bytes.chunks_mut(size).zip(&elements) {
+            element.serialize(&mut element_bytes)?;
+        }
+
+        map.flush_range(offset, batch_len * size)?;
+    }
+    Ok(())
+}
+
+/// Writes the header which precedes serialized `MPCParameters` for the curve `E`
+fn write_header<E: PairingEngine, W: Write>(writer: &mut W) -> Result<()> {
+    writer.write_all(&PARAMS_MAGIC)?;
+    writer.write_all(&[PARAMS_FORMAT_VERSION])?;
+    writer.write_all(&curve_id::<E>())?;
+    Ok(())
+}
+
+/// Reads the header which precedes serialized `MPCParameters`, checking that they
+/// were written in the current format version for the curve `E`.
+pub fn read_header<E: PairingEngine, R: Read>(reader: &mut R) -> Result<()> {
+    let mut header = [0u8; PARAMS_HEADER_SIZE];
+    read_exact_ctx(reader, &mut header, "header")?;
+    if header[..4] != PARAMS_MAGIC {
+        return Err(Phase2Error::InvalidMagic.into());
+    }
+    if header[4] != PARAMS_FORMAT_VERSION {
+        return Err(Phase2Error::UnsupportedFormatVersion {
+            expected: PARAMS_FORMAT_VERSION,
+            got: header[4],
+        }
+        .into());
+    }
+    let expected = curve_id::<E>();
+    if header[5..] != expected {
+        return Err(Phase2Error::WrongCurve {
+            expected: hex::encode(expected),
+            got: hex::encode(&header[5..]),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Returns the identifier of the curve `E` which is written in the header of
+/// serialized `MPCParameters`: the first 8 bytes of the BLAKE2b hash of the
+/// generators of G1 and G2, which differ between curves.
+pub fn curve_id<E: PairingEngine>() -> [u8; 8] {
+    let mut sink = HashWriter::new(io::sink());
+    E::G1Affine::prime_subgroup_generator()
+        .serialize(&mut sink)
+        .expect("serializing into a sink cannot fail");
+    E::G2Affine::prime_subgroup_generator()
+        .serialize(&mut sink)
+        .expect("serializing into a sink cannot fail");
+    let hash = sink.into_hash();
+    let mut id = [0u8; 8];
+    id.copy_from_slice(&hash[..8]);
+    id
+}
+
+/// Writes a length-prefixed vector of elements one element at a time
+fn write_query<C: AffineCurve, W: Write>(writer: &mut W, query: &[C]) -> Result<()> {
+    (query.len() as u64).serialize(&mut *writer)?;
+    for element in query {
+        element.serialize(&mut *writer)?;
+    }
+    Ok(())
+}
+
+/// This is a cheap helper utility that exists purely
+/// because Rust still doesn't have type-level integers
+/// and so doesn't implement `PartialEq` for `[T; HASH_LEN]`
+pub fn contains_contribution(contributions: &[[u8; HASH_LEN]], my_contribution: &[u8; HASH_LEN]) -> bool {
+    position_of_contribution(contributions, my_contribution).is_some()
+}
+
+/// Returns whether the contribution with hash `my_hash` is part of the serialized
+/// parameters in `params_reader`, which is the quick check a participant needs to
+/// know whether their contribution made it. Only the header, the verifying key and
+/// the transcript are decoded: the query vectors are skipped over without checking
+/// their points. Unlike `verify`, this does not check that the contributions are
+/// valid.
+pub fn confirm_contribution<E: PairingEngine, R: Read>(mut params_reader: R, my_hash: &[u8; HASH_LEN]) -> Result<bool> {
+    let reader = &mut params_reader;
+    read_header::<E, _>(reader)?;
+    VerifyingKey::<E>::deserialize(&mut *reader)?;
+    // beta_g1 and delta_g1
+    skip_bytes(reader, 2 * E::G1Affine::SERIALIZED_SIZE, "proving key")?;
+    for &size in &[
+        E::G1Affine::SERIALIZED_SIZE,
+        E::G1Affine::SERIALIZED_SIZE,
+        E::G2Affine::SERIALIZED_SIZE,
+        E::G1Affine::SERIALIZED_SIZE,
+        E::G1Affine::SERIALIZED_SIZE,
+    ] {
+        let len = u64::deserialize(&mut *reader)? as usize;
+        skip_bytes(reader, query_byte_len(len, size)? as usize, "proving key")?;
+    }
+    // the cs_hash and the phase 1 digest
+    skip_bytes(reader, 2 * HASH_LEN, "cs_hash")?;
+
+    let contributions = PublicKey::<E>::read_batch(reader)?;
+    let hashes = contributions.iter().map(|pubkey| pubkey.hash()).collect::<Vec<_>>();
+    Ok(contains_contribution(&hashes, my_hash))
+}
+
+/// Reads and discards exactly `len` bytes from `reader`
+fn skip_bytes<R: Read>(reader: &mut R, len: usize, context: &'static str) -> Result<()> {
+    let skipped = io::copy(&mut reader.by_ref().take(len as u64), &mut io::sink())
+        .map_err(|cause| Phase2Error::Io { context, cause })?;
+    if skipped != len as u64 {
+        return Err(Phase2Error::Io {
+            context,
+            cause: io::ErrorKind::UnexpectedEof.into(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Returns the index of `target` in the provided contribution hashes, if any.
+/// Each hash is compared in constant time.
+pub fn position_of_contribution(contributions: &[[u8; HASH_LEN]], target: &[u8; HASH_LEN]) -> Option<usize> {
+    contributions
+        .iter()
+        .position(|contrib| bool::from(contrib[..].ct_eq(&target[..])))
+}
+
+// Helpers for invariant checking
+pub fn ensure_same_length<T, U>(a: &[T], b: &[U]) -> Result<()> {
+    if a.len() != b.len() {
+        return Err(Phase2Error::InvalidLength.into());
+    }
+    Ok(())
+}
+
+/// A contribution only updates the H and L queries, so if both of them are
+/// empty there is nothing to contribute to.
+pub fn ensure_contributable(h_query_len: usize, l_query_len: usize) -> Result<()> {
+    if h_query_len == 0 && l_query_len == 0 {
+        return Err(Phase2Error::EmptyQueries.into());
+    }
+    Ok(())
+}
+
+/// The A, B_G1 and B_G2 queries all have one element per variable, so a
+/// well-formed `ProvingKey` must have all three of them at the same length.
+pub fn ensure_query_lengths<E: PairingEngine>(params: &ProvingKey<E>) -> Result<()> {
+    let expected = params.a_query.len();
+    for &got in &[params.b_g1_query.len(), params.b_g2_query.len()] {
+        if got != expected {
+            return Err(Error::InvalidLength { expected, got });
+        }
+    }
+    Ok(())
+}
+
+/// Ensures that none of the elements are the point at infinity
+pub fn ensure_dense<C: AffineCurve>(elements: &[C]) -> Result<()> {
+    if elements.iter().any(|e| e.is_zero()) {
+        return Err(Error::PointAtInfinity);
+    }
+    Ok(())
+}
+
+/// Checks that the L query has no point at infinity, i.e. that the circuit
+/// has no unconstrained variables. The error reports the index of the first
+/// such element in the L query, which follows the order of the assembly's
+/// `at`/`bt`/`ct` rows after the `num_public_variables` first ones.
+pub fn check_dense_l<E: PairingEngine>(l: &[E::G1Affine]) -> Result<()> {
+    if let Some(index) = l.iter().position(|e| e.is_zero()) {
+        return Err(Phase2Error::UnconstrainedVariable { index }.into());
+    }
+    Ok(())
+}
+
+pub fn ensure_unchanged_vec<T: PartialEq>(before: &[T], after: &[T], kind: &InvariantKind) -> Result<()> {
+    ensure_unchanged_vec_at_offset(before, after, 0, kind)
+}
+
+/// Same as `ensure_unchanged_vec`, for vectors which start at element `offset`
+/// of a larger vector. The index of the first changed element is reported
+/// relative to the larger vector.
+pub fn ensure_unchanged_vec_at_offset<T: PartialEq>(
+    before: &[T],
+    after: &[T],
+    offset: usize,
+    kind: &InvariantKind,
+) -> Result<()> {
+    if before.len() != after.len() {
+        return Err(Phase2Error::InvalidLength.into());
+    }
+    if let Some(i) = before.iter().zip(after).position(|(before, after)| before != after) {
+        return Err(Phase2Error::BrokenInvariantAt {
+            kind: kind.clone(),
+            index: offset + i,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+pub fn ensure_unchanged<T: PartialEq>(before: T, after: T, kind: InvariantKind) -> Result<()> {
+    if before != after {
+        return Err(Phase2Error::BrokenInvariant(kind).into());
+    }
+    Ok(())
+}
+
+/// The outcome of verifying a single contribution of the transcript
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContributionAudit<E: PairingEngine> {
+    /// The contribution's hash, as returned by `MPCParameters::contribute`
+    pub hash: [u8; HASH_LEN],
+    /// The `E::Fqk` value which both sides of `e(old_delta, r_delta) == e(delta_after, r)`
+    /// equal, computed when checking the consistency of this contribution's G1 delta
+    pub delta_pairing: E::Fqk,
+}
+
+/// What a participant did in a contribution, as returned by
+/// `MPCParameters::contribute_with_receipt`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContributionReceipt<E: PairingEngine> {
+    /// The contribution's hash, as returned by `MPCParameters::contribute`
+    pub hash: [u8; HASH_LEN],
+    /// The delta in G1 after the contribution
+    pub delta_after: E::G1Affine,
+    /// The position of the contribution in the transcript
+    pub contribution_index: usize,
+    /// The hash of the circuit's constraint system
+    pub cs_hash: [u8; HASH_LEN],
+}
+
+impl<E: PairingEngine> ContributionReceipt<E> {
+    /// Checks that the contribution of this receipt is in `params`, whose transcript
+    /// was verified to have the contribution hashes `verified_hashes`, e.g. as
+    /// returned by `MPCParameters::verify`.
+    pub fn is_included_in(&self, params: &MPCParameters<E>, verified_hashes: &[[u8; HASH_LEN]]) -> bool {
+        bool::from(self.cs_hash[..].ct_eq(&params.cs_hash[..]))
+            && position_of_contribution(verified_hashes, &self.hash) == Some(self.contribution_index)
+            && params
+                .contributions
+                .get(self.contribution_index)
+                .map_or(false, |pubkey| pubkey.delta_after == self.delta_after)
+    }
+}
+
+/// A contribution of the transcript, as exported by `export_transcript_json`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    /// The position of the contribution in the transcript
+    pub index: usize,
+    /// The hex encoded hash of the contribution
+    pub hash: String,
+    /// The hex encoded compressed delta in G1 after the contribution
+    pub delta_after: String,
+}
+
+/// A machine-readable record of a verified ceremony. All hashes are hex encoded.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CeremonyAttestation {
+    /// The hash of the circuit's constraint system
+    pub cs_hash: String,
+    /// The BLAKE2b hash of the final verifying key
+    pub vk_hash: String,
+    /// The number of contributions to the ceremony
+    pub num_contributions: usize,
+    /// The hashes of the contributions, in the order in which they were made
+    pub contributions: Vec<String>,
+    /// The BLAKE2b hash of the final delta in G1 and G2
+    pub delta_commitment: String,
+}
+
+pub fn verify_transcript<E: PairingEngine>(
+    cs_hash: [u8; HASH_LEN],
+    contributions: &[PublicKey<E>],
+) -> Result<Vec<[u8; HASH_LEN]>> {
+    verify_transcript_suffix_batched(cs_hash, contributions, 0, &mut rand::thread_rng())
+}
+
+pub fn verify_transcript_with_audit<E: PairingEngine>(
+    cs_hash: [u8; HASH_LEN],
+    contributions: &[PublicKey<E>],
+) -> Result<Vec<ContributionAudit<E>>> {
+    verify_transcript_suffix(cs_hash, contributions, 0)
+}
+
+/// Same as `verify_transcript`, but assumes that the first `already_verified`
+/// contributions were verified before, e.g. when the previous upload was
+/// accepted. Only the new contributions are checked, starting from the delta of
+/// the last verified one. The hashes of all the contributions are returned.
+pub fn verify_transcript_incremental<E: PairingEngine>(
+    cs_hash: [u8; HASH_LEN],
+    contributions: &[PublicKey<E>],
+    already_verified: usize,
+) -> Result<Vec<[u8; HASH_LEN]>> {
+    if already_verified > contributions.len() {
+        return Err(Error::InvalidLength {
+            expected: contributions.len(),
+            got: already_verified,
+        });
+    }
+
+    let hashes = verify_transcript_suffix_batched(cs_hash, contributions, already_verified, &mut rand::thread_rng())?;
+    Ok(contributions[..already_verified]
+        .iter()
+        .map(|pubkey| pubkey.hash())
+        .chain(hashes)
+        .collect())
+}
+
+/// Verifies the contributions from index `start` onwards, against the ones before it
+fn verify_transcript_suffix<E: PairingEngine>(
+    cs_hash: [u8; HASH_LEN],
+    contributions: &[PublicKey<E>],
+    start: usize,
+) -> Result<Vec<ContributionAudit<E>>> {
+    // The transcript and the signature of knowledge of each contribution
+    // can be checked independently, but their errors are reported in the
+    // order of the contributions
+    let new_contributions = &contributions[start..];
+    let rs = cfg_iter!(new_contributions)
+        .enumerate()
+        .map(|(i, pubkey)| pubkey.signature_of_knowledge_point(cs_hash, &contributions[0..start + i]))
+        .collect::<Vec<_>>();
+
+    let mut result = vec![];
+    let mut old_delta = match start {
+        0 => E::G1Affine::prime_subgroup_generator(),
+        _ => contributions[start - 1].delta_after,
+    };
+    for (pubkey, r) in new_contributions.iter().zip(rs) {
+        let r = r?;
+        // Check the change with the previous G1 Delta is consistent
+        let delta_pairing = check_same_ratio_with_value::<E>(
+            &(old_delta, pubkey.delta_after),
+            &(r, pubkey.r_delta),
+            "Inconsistent G1 Delta",
+        )?;
+        old_delta = pubkey.delta_after;
+
+        result.push(ContributionAudit {
+            hash: pubkey.hash(),
+            delta_pairing,
+        });
+    }
+
+    Ok(result)
+}
+
+/// Same as `verify_transcript_suffix`, but all the pairing checks of the new contributions
+/// are batched with a random linear combination sampled from `rng` (see `batch_same_ratio`), so that a valid
+/// transcript only needs a single final exponentiation. Returns the hashes of the new
+/// contributions. Unlike the audit, this does not compute the pairing of each delta check.
+fn verify_transcript_suffix_batched<E: PairingEngine, R: Rng>(
+    cs_hash: [u8; HASH_LEN],
+    contributions: &[PublicKey<E>],
+    start: usize,
+    rng: &mut R,
+) -> Result<Vec<[u8; HASH_LEN]>> {
+    let new_contributions = &contributions[start..];
+    let rs = cfg_iter!(new_contributions)
+        .enumerate()
+        .map(|(i, pubkey)| pubkey.transcript_point(cs_hash, &contributions[0..start + i]))
+        .collect::<Vec<_>>();
+
+    // The equations are in the order in which `verify_transcript_suffix` checks them,
+    // the signature of knowledge and then the change of the G1 Delta of each
+    // contribution, so that the first failing one is the error it would report.
+    // A contribution with an invalid transcript ends the equations, and its error is
+    // only reported if all the previous equations hold.
+    let mut equations = Vec::with_capacity(2 * rs.len());
+    let mut transcript_error = None;
+    let mut old_delta = match start {
+        0 => E::G1Affine::prime_subgroup_generator(),
+        _ => contributions[start - 1].delta_after,
+    };
+    for (pubkey, r) in new_contributions.iter().zip(rs) {
+        let r = match r {
+            Ok(r) => r,
+            Err(err) => {
+                transcript_error = Some(err);
+                break;
+            }
+        };
+        equations.push(((pubkey.s, pubkey.s_delta), (r, pubkey.r_delta)));
+        equations.push(((old_delta, pubkey.delta_after), (r, pubkey.r_delta)));
+        old_delta = pubkey.delta_after;
+    }
+
+    let results = batch_same_ratio_with_rng::<E, _>(&equations, rng)?;
+    if let Some(i) = results.iter().position(|valid| !valid) {
+        let err = match i % 2 {
+            0 => "Incorrect signature of knowledge",
+            _ => "Inconsistent G1 Delta",
+        };
+        return Err(VerificationError::InvalidRatio(err).into());
+    }
+    if let Some(err) = transcript_error {
+        return Err(err);
+    }
+
+    Ok(new_contributions.iter().map(|pubkey| pubkey.hash()).collect())
 }
 
 #[allow(unused)]
-fn hash_params<E: PairingEngine>(params: &ProvingKey<E>) -> Result<[u8; 64]> {
+fn hash_params<E: PairingEngine>(params: &ProvingKey<E>) -> Result<[u8; HASH_LEN]> {
     let sink = io::sink();
     let mut sink = HashWriter::new(sink);
     params.serialize(&mut sink)?;
-    let h = sink.into_hash();
-    let mut cs_hash = [0; 64];
-    cs_hash.copy_from_slice(h.as_ref());
-    Ok(cs_hash)
+    Ok(sink.into_digest())
+}
+
+/// Returns a hash identifying the QAP of a circuit and the phase 1 inputs, where
+/// `groth_params_digest` is a digest of the `Groth16Params` the parameters are
+/// created from. Two coordinators can compare it to check that they target the
+/// same circuit without running `MPCParameters::new`. This is not the same value
+/// as `MPCParameters::cs_hash`, which hashes the evaluated proving key.
+pub fn cs_hash_from_assembly<E: PairingEngine>(
+    assembly: &KeypairAssembly<E>,
+    groth_params_digest: [u8; HASH_LEN],
+) -> [u8; HASH_LEN] {
+    let sink = io::sink();
+    let mut sink = HashWriter::new(sink);
+    sink.write_all(&groth_params_digest).unwrap();
+    // the assembly's serialization contains the number of public and private
+    // variables, followed by the `at`, `bt` and `ct` matrices
+    assembly
+        .serialize(&mut sink)
+        .expect("serializing the KeypairAssembly should not fail");
+    sink.into_digest()
+}
+
+/// Returns the number of bytes `MPCParameters::write_compressed` writes for new
+/// parameters, i.e. without any contributions, so that a file can be allocated
+/// before it is written. `num_variables` is the number of variables of the QAP,
+/// i.e. the length of the A and B queries, including the `num_public` inputs and
+/// the "one" input `circuit_to_qap` allocates.
+pub fn phase2_file_size<E: PairingEngine>(
+    num_public: usize,
+    num_variables: usize,
+    phase2_size: usize,
+    compression: UseCompression,
+) -> Result<usize> {
+    if phase2_size == 0 {
+        return Err(Phase2Error::PhaseSizeTooSmall { needed: 1, got: 0 }.into());
+    }
+    let num_private = num_variables
+        .checked_sub(num_public)
+        .ok_or(Phase2Error::InvalidDimensions {
+            num_public,
+            num_variables,
+        })?;
+
+    let (g1_size, g2_size) = match compression {
+        UseCompression::Yes => (E::G1Affine::SERIALIZED_SIZE, E::G2Affine::SERIALIZED_SIZE),
+        UseCompression::No => (E::G1Affine::UNCOMPRESSED_SIZE, E::G2Affine::UNCOMPRESSED_SIZE),
+    };
+    // the dimensions are supplied by the caller, so the sizes are computed
+    // with checked arithmetic
+    let sizes = [
+        // alpha_g1, beta_g2, gamma_g2, delta_g2, beta_g1 and delta_g1
+        Some(3 * g1_size + 3 * g2_size),
+        // gamma_abc_g1 and the A, B, H and L queries
+        num_public.checked_mul(g1_size),
+        num_variables.checked_mul(g1_size),
+        num_variables.checked_mul(g1_size),
+        num_variables.checked_mul(g2_size),
+        (phase2_size - 1).checked_mul(g1_size),
+        num_private.checked_mul(g1_size),
+        // each of these vectors is prefixed by its length as a u64
+        Some(5 * u64::SERIALIZED_SIZE),
+        // the header, the cs_hash, the phase 1 digest and the empty contributions batch
+        Some(PARAMS_HEADER_SIZE + 2 * HASH_LEN + PublicKey::<E>::batch_size(&[])),
+    ];
+    sizes
+        .iter()
+        .try_fold(0usize, |total, size| size.and_then(|size| total.checked_add(size)))
+        .ok_or_else(|| Phase2Error::SizeOverflow.into())
+}
+
+/// Returns the number of constraints of a QAP. The `at`, `bt` and `ct` matrices
+/// of a `KeypairAssembly` have one row per variable, each holding the
+/// `(coefficient, constraint index)` pairs of the constraints it appears in,
+/// so this is one more than the largest constraint index.
+#[cfg(not(feature = "wasm"))]
+fn qap_num_constraints<E: PairingEngine>(assembly: &KeypairAssembly<E>) -> usize {
+    assembly
+        .at
+        .iter()
+        .chain(assembly.bt.iter())
+        .chain(assembly.ct.iter())
+        .flatten()
+        .map(|&(_, constraint)| constraint + 1)
+        .max()
+        .unwrap_or(0)
 }
 
-/// Converts an R1CS circuit to QAP form
+/// Converts an R1CS circuit to QAP form. With the `unsafe-fast-qap` feature,
+/// if `E` and `Zexe` are the same engine, the assembly is returned as is instead
+/// of being converted through a serialization round-trip.
 pub fn circuit_to_qap<E: PairingEngine, Zexe: PairingEngine, C: ConstraintSynthesizer<E::Fr>>(
     circuit: C,
 ) -> Result<KeypairAssembly<Zexe>> {
@@ -414,6 +2088,19 @@ pub fn circuit_to_qap<E: PairingEngine, Zexe: PairingEngine, C: ConstraintSynthe
         );
     }
 
+    // If the circuit was synthesized over the target engine there is nothing to convert.
+    // Downcasting is only possible when both types are the same, so no unsafe code is needed.
+    #[cfg(feature = "unsafe-fast-qap")]
+    let assembly = {
+        let assembly: Box<dyn Any> = Box::new(assembly);
+        match assembly.downcast::<KeypairAssembly<Zexe>>() {
+            Ok(assembly) => return Ok(*assembly),
+            Err(assembly) => *assembly
+                .downcast::<KeypairAssembly<E>>()
+                .expect("the assembly was created over E"),
+        }
+    };
+
     // We now serialize it as a vector and deserialize it as a snarkVM keypair assembly
     // (we do uncompressed because it is faster)
     // (This could alternatively be done with unsafe memory swapping, but we
@@ -427,23 +2114,172 @@ pub fn circuit_to_qap<E: PairingEngine, Zexe: PairingEngine, C: ConstraintSynthe
     Ok(assembly)
 }
 
-#[cfg(test)]
+/// Returns the `(num_public, num_private, num_constraints)` dimensions of the QAP
+/// `circuit_to_qap` would produce for `circuit`, including the "one" input and the
+/// constraints added for each public input, without building the QAP. This is cheap
+/// enough to pick a `phase2_size` before running phase 2, which needs at least
+/// `max(num_constraints, num_public + num_private)` coefficients, the same bound
+/// setup2 uses for its ceremony size.
+pub fn circuit_dimensions<E: PairingEngine, C: ConstraintSynthesizer<E::Fr>>(
+    circuit: &C,
+) -> Result<(usize, usize, usize)> {
+    let mut counter = ConstraintCounter {
+        num_public_variables: 0,
+        num_private_variables: 0,
+        num_constraints: 0,
+    };
+
+    // Allocate the "one" input variable
+    counter.alloc_input(|| "", || Ok(E::Fr::one()))?;
+    // Synthesize the circuit
+    circuit.generate_constraints(&mut counter)?;
+    // Input constraints to ensure full density of IC query
+    let num_constraints = counter.num_constraints + counter.num_public_variables;
+
+    Ok((
+        counter.num_public_variables,
+        counter.num_private_variables,
+        num_constraints,
+    ))
+}
+
+/// Checks that `pk` can actually be used for proving `circuit`: the length of
+/// its queries must match the dimensions of the circuit, and a proof for the
+/// assignment carried by `circuit` must verify against the key's own verifying
+/// key with `public_inputs`, which exclude the implicit "one" input. This catches
+/// a truncated or mismatched key, e.g. a finalized key which was stripped of its
+/// transcript, before it is deployed.
+pub fn sanity_check_proving_key<E: PairingEngine, C: ConstraintSynthesizer<E::Fr>>(
+    pk: &ProvingKey<E>,
+    circuit: &C,
+    public_inputs: &[E::Fr],
+) -> Result<()> {
+    let (num_public, num_private, _) = circuit_dimensions::<E, _>(circuit)?;
+    let lengths = [
+        ("gamma_abc_g1", num_public, pk.vk.gamma_abc_g1.len()),
+        ("a_query", num_public + num_private, pk.a_query.len()),
+        ("b_g1_query", num_public + num_private, pk.b_g1_query.len()),
+        ("b_g2_query", num_public + num_private, pk.b_g2_query.len()),
+        ("l_query", num_private, pk.l_query.len()),
+    ];
+    for &(field, expected, got) in lengths.iter() {
+        if got != expected {
+            return Err(Phase2Error::UnexpectedLength { field, expected, got }.into());
+        }
+    }
+
+    let proof = create_random_proof(circuit, pk, &mut rand::thread_rng())?;
+    let pvk = prepare_verifying_key(pk.vk.clone());
+    if !verify_proof(&pvk, &proof, public_inputs)? {
+        return Err(Phase2Error::UnusableProvingKey.into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        chunked_groth16::{contribute, verify},
+        chunked_groth16::{
+            contribute,
+            contribute_chunk,
+            contribute_resumable,
+            ensure_ranges_cover,
+            resume_contribution,
+            verify,
+            verify_chunk_range,
+            ContributionCheckpoint,
+        },
         helpers::testing::TestCircuit,
+        keypair::hash_cs_pubkeys,
     };
     use phase1::{helpers::testing::setup_verify, Phase1, Phase1Parameters, ProvingSystem};
     use setup_utils::{Groth16Params, UseCompression};
-    use snarkvm_curves::bls12_377::Bls12_377;
+    use snarkvm_algorithms::hash_to_curve::hash_to_curve;
+    use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761};
+    use snarkvm_utilities::UniformRand;
 
     use rand::thread_rng;
     use tracing_subscriber::{filter::EnvFilter, fmt::Subscriber};
 
+    #[test]
+    fn new_with_small_phase2_size_fails() {
+        new_with_small_phase2_size_fails_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn new_with_small_phase2_size_fails_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let powers = 5;
+        let batch = 16;
+        // the circuit has 7 variables, which do not fit in 4 coefficients
+        let phase2_size = 4;
+        let params = Phase1Parameters::<E>::new_full(ProvingSystem::Groth16, powers, batch);
+        let compressed = UseCompression::No;
+        let (_, output, _, _) = setup_verify(compressed, CheckForCorrectness::Full, compressed, &params);
+        let accumulator = Phase1::deserialize(&output, compressed, CheckForCorrectness::Full, &params).unwrap();
+        let groth_params = Groth16Params::<E>::new(
+            phase2_size,
+            accumulator.tau_powers_g1,
+            accumulator.tau_powers_g2,
+            accumulator.alpha_tau_powers_g1,
+            accumulator.beta_tau_powers_g1,
+            accumulator.beta_g2,
+        )
+        .unwrap();
+
+        let assembly = circuit_to_qap::<Aleo, E, _>(TestCircuit::<Aleo>(None)).unwrap();
+        let err = MPCParameters::new(assembly, groth_params).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::PhaseSizeTooSmall { needed: 7, got: 4 })
+        ));
+    }
+
+    #[test]
+    fn qap_layout() {
+        qap_layout_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn qap_layout_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let assembly = circuit_to_qap::<Aleo, E, _>(TestCircuit::<Aleo>(None)).unwrap();
+        // the QAP has one row per variable: the "one" input, the input,
+        // x and the 4 dummy variables
+        assert_eq!((assembly.num_public_variables, assembly.num_private_variables), (2, 5));
+        assert_eq!(assembly.at.len(), 7);
+        // and 6 constraints: the 4 of the circuit and one per input
+        assert_eq!(qap_num_constraints(&assembly), 6);
+    }
+
+    #[test]
+    fn check_dense_l() {
+        check_dense_l_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn check_dense_l_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut l = mpc.params.l_query.clone();
+        super::check_dense_l::<E>(&l).unwrap();
+
+        l[2] = E::G1Affine::zero();
+        let err = super::check_dense_l::<E>(&l).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::UnconstrainedVariable { index: 2 })
+        ));
+
+        // the first one is reported
+        l[1] = E::G1Affine::zero();
+        let err = super::check_dense_l::<E>(&l).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::UnconstrainedVariable { index: 1 })
+        ));
+    }
+
     #[test]
     fn serialize_ceremony() {
-        serialize_ceremony_curve::<Bls12_377, Bls12_377>()
+        serialize_ceremony_curve::<Bls12_377, Bls12_377>();
+        serialize_ceremony_curve::<BW6_761, BW6_761>();
     }
 
     fn serialize_ceremony_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
@@ -458,34 +2294,2080 @@ mod tests {
     }
 
     #[test]
-    fn verify_with_self_fails() {
-        verify_with_self_fails_curve::<Bls12_377, Bls12_377>()
+    fn write_proving_key_only() {
+        write_proving_key_only_curve::<Bls12_377, Bls12_377>()
     }
 
-    // if there has been no contribution
-    // then checking with itself should fail
-    fn verify_with_self_fails_curve<Aleo: PairingEngine, E: PairingEngine>() {
+    fn write_proving_key_only_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.contribute(&mut thread_rng()).unwrap();
+
+        let mut writer = vec![];
+        mpc.write_proving_key_only(&mut writer).unwrap();
+        let proving_key = ProvingKey::<E>::deserialize(&mut &writer[..]).unwrap();
+        assert_eq!(proving_key, mpc.clone().into_proving_key());
+        assert_eq!(&proving_key, mpc.get_params());
+    }
+
+    #[test]
+    fn write_verifying_key() {
+        write_verifying_key_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn write_verifying_key_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.contribute(&mut thread_rng()).unwrap();
+
+        let mut written = vec![];
+        mpc.write_verifying_key(&mut written).unwrap();
+        let mut expected = vec![];
+        mpc.params.vk.serialize(&mut expected).unwrap();
+        assert_eq!(written, expected);
+
+        let vk = MPCParameters::<E>::read_verifying_key(&written[..]).unwrap();
+        assert_eq!(vk, mpc.params.vk);
+    }
+
+    #[test]
+    fn prepared_verifying_key() {
+        prepared_verifying_key_curve::<Bls12_377>()
+    }
+
+    fn prepared_verifying_key_curve<E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mut mpc = generate_ceremony::<E, E>();
+        mpc.contribute(rng).unwrap();
+        let pvk = mpc.prepared_verifying_key();
+
+        // prove that we know the square root of 25
+        let c = TestCircuit::<E>(Some(E::Fr::from(5u8)));
+        let proof = create_random_proof(&c, &mpc.params, rng).unwrap();
+        assert!(verify_proof(&pvk, &proof, &[E::Fr::from(25u8)]).unwrap());
+        assert!(!verify_proof(&pvk, &proof, &[E::Fr::from(24u8)]).unwrap());
+    }
+
+    #[test]
+    fn write_compressed() {
+        write_compressed_curve::<Bls12_377, Bls12_377>();
+        write_compressed_curve::<BW6_761, BW6_761>();
+    }
+
+    fn write_compressed_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
         let mpc = generate_ceremony::<Aleo, E>();
-        let err = mpc.verify(&mpc);
-        // we handle the error like this because [u8; 64] does not implement
-        // debug, meaning we cannot call `assert` on it
-        if let Err(e) = err {
-            assert_eq!(e.to_string(), "Phase 2 Error: There were no contributions found");
-        } else {
-            panic!("Verifying with self must fail")
+
+        let mut compressed = vec![];
+        mpc.write_compressed(&mut compressed, UseCompression::Yes).unwrap();
+        let mut uncompressed = vec![];
+        mpc.write_compressed(&mut uncompressed, UseCompression::No).unwrap();
+        assert!(compressed.len() < uncompressed.len());
+
+        let mut written = vec![];
+        mpc.write(&mut written).unwrap();
+        assert_eq!(compressed, written);
+
+        let deserialized = MPCParameters::<E>::read_compressed(&compressed[..], UseCompression::Yes).unwrap();
+        assert_eq!(deserialized, mpc);
+        let deserialized = MPCParameters::<E>::read_compressed(&uncompressed[..], UseCompression::No).unwrap();
+        assert_eq!(deserialized, mpc);
+    }
+
+    #[test]
+    fn recompress() {
+        recompress_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn recompress_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.contribute(&mut thread_rng()).unwrap();
+        let mut compressed = vec![];
+        mpc.write_compressed(&mut compressed, UseCompression::Yes).unwrap();
+
+        let mut uncompressed = vec![];
+        MPCParameters::<E>::recompress(
+            &compressed[..],
+            &mut uncompressed,
+            UseCompression::Yes,
+            UseCompression::No,
+        )
+        .unwrap();
+        let deserialized = MPCParameters::<E>::read_compressed(&uncompressed[..], UseCompression::No).unwrap();
+        assert_eq!(deserialized, mpc);
+
+        let mut recompressed = vec![];
+        MPCParameters::<E>::recompress(
+            &uncompressed[..],
+            &mut recompressed,
+            UseCompression::No,
+            UseCompression::Yes,
+        )
+        .unwrap();
+        assert_eq!(recompressed, compressed);
+    }
+
+    #[test]
+    fn serialized_size() {
+        serialized_size_curve::<Bls12_377, Bls12_377>();
+        serialized_size_curve::<BW6_761, BW6_761>();
+    }
+
+    fn serialized_size_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.contribute(&mut thread_rng()).unwrap();
+
+        let mut written = vec![];
+        mpc.write(&mut written).unwrap();
+        assert_eq!(mpc.serialized_size(UseCompression::Yes), written.len());
+
+        let mut uncompressed = vec![];
+        mpc.write_compressed(&mut uncompressed, UseCompression::No).unwrap();
+        assert_eq!(mpc.serialized_size(UseCompression::No), uncompressed.len());
+    }
+
+    #[test]
+    fn cs_hash_from_assembly() {
+        cs_hash_from_assembly_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn cs_hash_from_assembly_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let digest = [3u8; HASH_LEN];
+        let assembly = circuit_to_qap::<Aleo, E, _>(TestCircuit::<Aleo>(None)).unwrap();
+        let hash = super::cs_hash_from_assembly(&assembly, digest);
+
+        // the same circuit and phase 1 inputs give the same hash
+        let other = circuit_to_qap::<Aleo, E, _>(TestCircuit::<Aleo>(None)).unwrap();
+        assert_eq!(super::cs_hash_from_assembly(&other, digest), hash);
+
+        // other phase 1 inputs or constraints do not
+        assert_ne!(super::cs_hash_from_assembly(&other, [4u8; HASH_LEN]), hash);
+        let mut modified = other;
+        let (coeff, index) = modified.at[0][0];
+        modified.at[0][0] = (coeff.double(), index);
+        assert_ne!(super::cs_hash_from_assembly(&modified, digest), hash);
+    }
+
+    #[test]
+    fn read_with_dimensions() {
+        read_with_dimensions_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn read_with_dimensions_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut writer = vec![];
+        mpc.write(&mut writer).unwrap();
+
+        let dimensions = ParamDimensions::of(&mpc.params);
+        let deserialized = MPCParameters::<E>::read_with_dimensions(&writer[..], &dimensions).unwrap();
+        assert_eq!(deserialized, mpc);
+
+        // parameters for a larger phase 2 size are rejected at the H query
+        let larger = ParamDimensions {
+            phase2_size: dimensions.phase2_size * 2,
+            ..dimensions
+        };
+        let err = MPCParameters::<E>::read_with_dimensions(&writer[..], &larger).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::UnexpectedLength { field: "h_query", expected, got })
+                if expected == larger.phase2_size - 1 && got == dimensions.phase2_size - 1
+        ));
+
+        let fewer_variables = ParamDimensions {
+            num_variables: dimensions.num_variables - 1,
+            ..dimensions
+        };
+        let err = MPCParameters::<E>::read_with_dimensions(&writer[..], &fewer_variables).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::UnexpectedLength { field: "a_query", .. })
+        ));
+    }
+
+    #[test]
+    fn phase2_file_size() {
+        phase2_file_size_curve::<Bls12_377, Bls12_377>();
+        phase2_file_size_curve::<BW6_761, BW6_761>();
+    }
+
+    fn phase2_file_size_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let assembly = circuit_to_qap::<Aleo, E, _>(TestCircuit::<Aleo>(None)).unwrap();
+        let num_public = assembly.num_public_variables;
+        let num_variables = num_public + assembly.num_private_variables;
+
+        for &compression in &[UseCompression::Yes, UseCompression::No] {
+            let mut written = vec![];
+            mpc.write_compressed(&mut written, compression).unwrap();
+            assert_eq!(
+                super::phase2_file_size::<E>(num_public, num_variables, 7, compression).unwrap(),
+                written.len()
+            );
+        }
+
+        // the dimensions are checked instead of underflowing
+        let err = super::phase2_file_size::<E>(num_public, 1, 7, UseCompression::Yes).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::InvalidDimensions { num_variables: 1, .. })
+        ));
+        let err = super::phase2_file_size::<E>(num_public, num_variables, 0, UseCompression::Yes).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::PhaseSizeTooSmall { needed: 1, got: 0 })
+        ));
+    }
+
+    #[test]
+    fn sanity_check_proving_key() {
+        sanity_check_proving_key_curve::<Bls12_377>()
+    }
+
+    fn sanity_check_proving_key_curve<E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mut mpc = generate_ceremony::<E, E>();
+        mpc.contribute(rng).unwrap();
+        let circuit = TestCircuit::<E>(Some(E::Fr::from(5u8)));
+        let inputs = [E::Fr::from(25u8)];
+        super::sanity_check_proving_key(&mpc.params, &circuit, &inputs).unwrap();
+
+        // a corrupted element of the L query produces invalid proofs
+        let mut corrupted = mpc.params.clone();
+        corrupted.l_query[0] = corrupted.l_query[1];
+        let err = super::sanity_check_proving_key(&corrupted, &circuit, &inputs).unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::UnusableProvingKey)));
+
+        // a truncated one is rejected before proving
+        let mut truncated = mpc.params.clone();
+        truncated.l_query.pop();
+        let err = super::sanity_check_proving_key(&truncated, &circuit, &inputs).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::UnexpectedLength { field: "l_query", .. })
+        ));
+    }
+
+    #[test]
+    fn circuit_dimensions() {
+        circuit_dimensions_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn circuit_dimensions_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let (num_public, num_private, num_constraints) =
+            super::circuit_dimensions::<Aleo, _>(&TestCircuit::<Aleo>(None)).unwrap();
+        // the "one" input and the square, x and the 4 dummy variables,
+        // the 4 constraints of the circuit and one per input
+        assert_eq!((num_public, num_private, num_constraints), (2, 5, 6));
+        // which fit in the phase 2 size of 7 used by the tests
+        assert_eq!(max(num_constraints, num_public + num_private), 7);
+
+        let assembly = circuit_to_qap::<Aleo, E, _>(TestCircuit::<Aleo>(None)).unwrap();
+        assert_eq!(num_public, assembly.num_public_variables);
+        assert_eq!(num_private, assembly.num_private_variables);
+        // the QAP has one row per variable, not per constraint
+        assert_eq!(num_public + num_private, assembly.at.len());
+        assert_eq!(num_constraints, qap_num_constraints(&assembly));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn read_async() {
+        read_async_curve::<Bls12_377, Bls12_377>().await
+    }
+
+    #[cfg(feature = "async")]
+    async fn read_async_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.contribute(&mut thread_rng()).unwrap();
+        let mut serialized = vec![];
+        mpc.write(&mut serialized).unwrap();
+
+        let max_len = serialized.len();
+        let deserialized = MPCParameters::<E>::read_async(io::Cursor::new(serialized.clone()), max_len)
+            .await
+            .unwrap();
+        assert_eq!(deserialized, mpc);
+
+        // truncated uploads fail like they do with `read`
+        MPCParameters::<E>::read_async(&serialized[..serialized.len() - 1], max_len)
+            .await
+            .unwrap_err();
+
+        // uploads larger than the limit are rejected without being read entirely
+        let err = MPCParameters::<E>::read_async(&serialized[..], max_len - 1)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::InputTooLarge { max_len: got }) if got == max_len - 1
+        ));
+    }
+
+    #[test]
+    fn write_with_checksum() {
+        write_with_checksum_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn write_with_checksum_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut writer = vec![];
+        mpc.write_with_checksum(&mut writer).unwrap();
+
+        let deserialized = MPCParameters::<E>::read_with_checksum(&writer[..]).unwrap();
+        assert_eq!(deserialized, mpc);
+
+        // flipping a byte of the body is caught by the checksum
+        let mut corrupted = writer.clone();
+        corrupted[100] ^= 1;
+        let err = MPCParameters::<E>::read_with_checksum(&corrupted[..]).unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::ChecksumMismatch { .. })));
+
+        // and so is a truncated file
+        MPCParameters::<E>::read_with_checksum(&writer[..writer.len() - 1]).unwrap_err();
+    }
+
+    #[test]
+    fn hash_len_is_consistent() {
+        hash_len_is_consistent_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn hash_len_is_consistent_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let rng = &mut thread_rng();
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut contribution = mpc.clone();
+        let hash = contribution.contribute(rng).unwrap();
+
+        // the cs_hash, the phase 1 digest and the transcript take `HASH_LEN` bytes each
+        let mut serialized = vec![];
+        contribution.write(&mut serialized).unwrap();
+        assert_eq!(serialized.len(), contribution.serialized_size(UseCompression::Yes));
+        let cs_hash_start = PARAMS_HEADER_SIZE + contribution.params.serialized_size();
+        assert_eq!(
+            &serialized[cs_hash_start..cs_hash_start + HASH_LEN],
+            &contribution.cs_hash[..]
+        );
+        assert_eq!(
+            &serialized[cs_hash_start + HASH_LEN..cs_hash_start + 2 * HASH_LEN],
+            &contribution.phase1_digest[..]
+        );
+        assert_eq!(
+            &serialized[serialized.len() - HASH_LEN..],
+            &contribution.contributions[0].transcript[..]
+        );
+        assert_eq!(
+            PublicKey::<E>::size(),
+            serialized.len() - cs_hash_start - 2 * HASH_LEN - 4
+        );
+
+        // and the hashes survive a round trip and verification
+        let deserialized = MPCParameters::<E>::read(&serialized[..]).unwrap();
+        assert_eq!(deserialized, contribution);
+        assert_eq!(mpc.verify(&deserialized).unwrap(), vec![hash]);
+    }
+
+    #[test]
+    fn read_checks_header() {
+        read_checks_header_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn read_checks_header_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut serialized = vec![];
+        mpc.write(&mut serialized).unwrap();
+        assert_eq!(&serialized[..4], &PARAMS_MAGIC);
+        assert_eq!(serialized[4], PARAMS_FORMAT_VERSION);
+        assert_eq!(&serialized[5..PARAMS_HEADER_SIZE], &curve_id::<E>());
+        assert_eq!(MPCParameters::<E>::read(&serialized[..]).unwrap(), mpc);
+
+        // parameters written in another version of the format are rejected
+        let mut other_version = serialized.clone();
+        other_version[4] = PARAMS_FORMAT_VERSION + 1;
+        let err = MPCParameters::<E>::read(&other_version[..]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::UnsupportedFormatVersion { expected, got })
+                if expected == PARAMS_FORMAT_VERSION && got == PARAMS_FORMAT_VERSION + 1
+        ));
+
+        // ...as well as parameters for another curve
+        assert_ne!(curve_id::<Bls12_377>(), curve_id::<BW6_761>());
+        let err = MPCParameters::<BW6_761>::read(&serialized[..]).unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::WrongCurve { .. })));
+        let err = MPCParameters::<BW6_761>::read_compressed(&serialized[..], UseCompression::Yes).unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::WrongCurve { .. })));
+
+        // ...and files without a header
+        let mut headerless = vec![];
+        mpc.write_proving_key_only(&mut headerless).unwrap();
+        let err = MPCParameters::<E>::read(&headerless[..]).unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::InvalidMagic)));
+    }
+
+    #[test]
+    fn read_truncated_fails_with_context() {
+        read_truncated_fails_with_context_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn read_truncated_fails_with_context_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.contribute(&mut thread_rng()).unwrap();
+        let mut writer = vec![];
+        mpc.write(&mut writer).unwrap();
+        let params_len = PARAMS_HEADER_SIZE + mpc.params.serialized_size();
+
+        let context_of = |len: usize| match MPCParameters::<E>::read(&writer[..len]) {
+            Err(Error::Phase2Error(Phase2Error::Io { context, cause })) => {
+                assert_eq!(cause.kind(), io::ErrorKind::UnexpectedEof);
+                context
+            }
+            result => panic!("unexpected result {:?}", result.map(|_| ())),
+        };
+        // the file ends within the header
+        assert_eq!(context_of(PARAMS_HEADER_SIZE - 1), "header");
+        // the file ends right after the proving key or within the cs_hash
+        assert_eq!(context_of(params_len), "cs_hash");
+        assert_eq!(context_of(params_len + HASH_LEN / 2), "cs_hash");
+        // the file ends right after the cs_hash or within the phase 1 digest
+        assert_eq!(context_of(params_len + HASH_LEN), "phase1_digest");
+        assert_eq!(context_of(params_len + HASH_LEN + HASH_LEN / 2), "phase1_digest");
+        // the file ends before or within the number of contributions
+        assert_eq!(context_of(params_len + 2 * HASH_LEN), "contributions batch");
+        assert_eq!(context_of(params_len + 2 * HASH_LEN + 2), "contributions batch");
+        // the file ends within the transcript of the contribution
+        assert_eq!(context_of(writer.len() - 1), "contribution transcript");
+    }
+
+    #[test]
+    fn write_streaming() {
+        write_streaming_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn write_streaming_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.contribute(&mut thread_rng()).unwrap();
+
+        let mut expected = vec![];
+        mpc.write(&mut expected).unwrap();
+        let mut streamed = vec![];
+        mpc.write_streaming(&mut streamed).unwrap();
+        assert_eq!(streamed, expected);
+
+        let deserialized = MPCParameters::<E>::read(&streamed[..]).unwrap();
+        assert_eq!(deserialized, mpc);
+    }
+
+    #[test]
+    fn read_with_progress() {
+        read_with_progress_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn read_with_progress_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.contribute(&mut thread_rng()).unwrap();
+        let mut writer = vec![];
+        mpc.write(&mut writer).unwrap();
+
+        let expected_total = mpc.params.a_query.len()
+            + mpc.params.b_g1_query.len()
+            + mpc.params.b_g2_query.len()
+            + mpc.params.h_query.len()
+            + mpc.params.l_query.len();
+        let mut calls = vec![];
+        let deserialized = MPCParameters::<E>::read_with_progress(std::io::Cursor::new(&writer), |done, total| {
+            calls.push((done, total))
+        })
+        .unwrap();
+        assert_eq!(deserialized, mpc);
+
+        assert_eq!(calls.len(), expected_total);
+        for (i, (done, total)) in calls.into_iter().enumerate() {
+            assert_eq!(done, i + 1);
+            assert_eq!(total, expected_total);
         }
     }
+
+    #[test]
+    fn read_with_expected_digest() {
+        read_with_expected_digest_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn read_with_expected_digest_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut writer = vec![];
+        mpc.write(&mut writer).unwrap();
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(&Sha256::digest(&writer));
+
+        let deserialized = MPCParameters::<E>::read_with_expected_digest(&writer[..], digest).unwrap();
+        assert_eq!(deserialized, mpc);
+
+        let err = MPCParameters::<E>::read_with_expected_digest(&writer[..], [0; 32]).unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::DigestMismatch { .. })));
+
+        // trailing bytes are part of the digest
+        writer.push(0);
+        let err = MPCParameters::<E>::read_with_expected_digest(&writer[..], digest).unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::DigestMismatch { .. })));
+    }
+
+    #[test]
+    fn read_inconsistent_queries_fails() {
+        read_inconsistent_queries_fails_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn read_inconsistent_queries_fails_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.params.b_g1_query.pop();
+
+        let mut writer = vec![];
+        mpc.write(&mut writer).unwrap();
+        let err = MPCParameters::<E>::read(&writer[..]).unwrap_err();
+        let expected = mpc.params.a_query.len();
+        assert!(matches!(err, Error::InvalidLength { expected: e, got } if e == expected && got == expected - 1));
+    }
+
+    #[test]
+    fn contribute_to_empty_queries_fails() {
+        contribute_to_empty_queries_fails_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn contribute_to_empty_queries_fails_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.params.h_query.clear();
+        mpc.params.l_query.clear();
+
+        let mut serialized = vec![];
+        mpc.write(&mut serialized).unwrap();
+        serialized.resize(serialized.len() + PublicKey::<E>::size(), 0);
+        let before = serialized.clone();
+
+        let err = mpc.clone().contribute(rng).unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::EmptyQueries)));
+        let err = contribute::<E, _>(&mut serialized, rng, 4).unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::EmptyQueries)));
+        // the buffer must be left untouched
+        assert_eq!(before, serialized);
+    }
+
+    #[test]
+    fn verify_inconsistent_beta_fails() {
+        verify_inconsistent_beta_fails_curve::<Bls12_377, Bls12_377>()
+    }
+
+    // a beta which was corrupted identically in both
+    // steps is caught by the pairing check
+    fn verify_inconsistent_beta_fails_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.params.beta_g1 = mpc.params.beta_g1.mul(E::Fr::one().double());
+        let mut contribution = mpc.clone();
+        contribution.contribute(rng).unwrap();
+
+        let err = mpc.verify(&contribution).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::VerificationError(VerificationError::InvalidRatio("Inconsistent Beta"))
+        ));
+
+        let mut before = vec![];
+        mpc.write(&mut before).unwrap();
+        let mut after = vec![];
+        contribution.write(&mut after).unwrap();
+        let err = verify::<E>(&mut before, &mut after, 4).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::VerificationError(VerificationError::InvalidRatio("Inconsistent Beta"))
+        ));
+    }
+
+    #[test]
+    fn resume_interrupted_contribution() {
+        resume_interrupted_contribution_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn resume_interrupted_contribution_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut before = vec![];
+        mpc.write(&mut before).unwrap();
+        let mut buf = before.clone();
+        buf.resize(buf.len() + PublicKey::<E>::size(), 0);
+
+        // get "killed" right after the 3rd checkpoint was persisted,
+        // i.e. before its batch was written to the buffer
+        let mut saved = vec![];
+        let mut calls = 0;
+        let err = contribute_resumable::<E, _, _>(&mut buf, rng, 2, |checkpoint| {
+            calls += 1;
+            saved.clear();
+            checkpoint.write(&mut saved)?;
+            if calls == 3 {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "killed").into());
+            }
+            Ok(())
+        })
+        .unwrap_err();
+        assert!(matches!(err, Error::IoError(_)));
+
+        let checkpoint = ContributionCheckpoint::<E>::read(&mut &saved[..]).unwrap();
+        let hash = resume_contribution(&mut buf, checkpoint, 2, |_| Ok(())).unwrap();
+
+        let hashes = verify::<E>(&mut before, &mut buf, 4).unwrap();
+        assert!(contains_contribution(&hashes, &hash));
+        let after = MPCParameters::<E>::read(&buf[..]).unwrap();
+        mpc.verify(&after).unwrap();
+    }
+
+    #[test]
+    fn confirm_contribution() {
+        confirm_contribution_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn confirm_contribution_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        let first = mpc.contribute(rng).unwrap();
+        let second = mpc.contribute(rng).unwrap();
+        let mut serialized = vec![];
+        mpc.write(&mut serialized).unwrap();
+
+        assert!(super::confirm_contribution::<E, _>(&serialized[..], &first).unwrap());
+        assert!(super::confirm_contribution::<E, _>(&serialized[..], &second).unwrap());
+        assert!(!super::confirm_contribution::<E, _>(&serialized[..], &[7u8; HASH_LEN]).unwrap());
+
+        // a truncated file is an error rather than a missing contribution
+        let truncated = &serialized[..PARAMS_HEADER_SIZE + 100];
+        super::confirm_contribution::<E, _>(truncated, &first).unwrap_err();
+
+        // and so is a query length whose size in bytes overflows
+        let a_len_start = PARAMS_HEADER_SIZE + mpc.params.vk.serialized_size() + 2 * E::G1Affine::SERIALIZED_SIZE;
+        serialized[a_len_start..a_len_start + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        let err = super::confirm_contribution::<E, _>(&serialized[..], &first).unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::SizeOverflow)));
+    }
+
+    #[test]
+    fn contribution_hashes() {
+        contribution_hashes_curve::<Bls12_377, Bls12_377>();
+        contribution_hashes_curve::<BW6_761, BW6_761>();
+    }
+
+    fn contribution_hashes_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mpc = generate_ceremony::<Aleo, E>();
+        assert!(mpc.contribution_hashes().is_empty());
+
+        let mut contribution = mpc.clone();
+        let hash1 = contribution.contribute(rng).unwrap();
+        let hash2 = contribution.contribute(rng).unwrap();
+
+        let hashes = contribution.contribution_hashes();
+        assert!(contains_contribution(&hashes, &hash1));
+        assert!(contains_contribution(&hashes, &hash2));
+        assert_eq!(hashes, mpc.verify(&contribution).unwrap());
+    }
+
+    #[test]
+    fn corrupt_delta() {
+        corrupt_delta_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn corrupt_delta_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mpc = generate_ceremony::<Aleo, E>();
+
+        let mut contribution = mpc.clone();
+        contribution.contribute(rng).unwrap();
+        contribution.corrupt_delta(rng);
+
+        let err = mpc.verify(&contribution).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::VerificationError(VerificationError::InvalidRatio("H_query ratio check failed"))
+        ));
+    }
+
+    #[test]
+    fn contribute_with_identity() {
+        contribute_with_identity_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn contribute_with_identity_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let rng = &mut thread_rng();
+        let mpc = generate_ceremony::<Aleo, E>();
+
+        let mut contribution = mpc.clone();
+        contribution.contribute(rng).unwrap();
+        let hash = contribution.contribute_with_identity(b"alice", rng).unwrap();
+        assert_eq!(contribution.contributions[1].identity(), Some(&b"alice"[..]));
+        assert_eq!(mpc.verify(&contribution).unwrap()[1], hash);
+
+        // the identity survives serialization
+        let mut writer = vec![];
+        contribution.write(&mut writer).unwrap();
+        assert_eq!(contribution.serialized_size(UseCompression::Yes), writer.len());
+        let deserialized = MPCParameters::<E>::read(&writer[..]).unwrap();
+        assert_eq!(deserialized, contribution);
+
+        // changing the recorded identity breaks the transcript
+        let mut tampered = contribution.clone();
+        tampered.contributions[1].set_identity(Some(b"mallory".to_vec()));
+        let err = mpc.verify(&tampered).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::BrokenInvariant(InvariantKind::Transcript))
+        ));
+        tampered.contributions[1].set_identity(None);
+        mpc.verify(&tampered).unwrap_err();
+    }
+
+    #[test]
+    fn contribute_in_place_after_identity() {
+        contribute_in_place_after_identity_curve::<Bls12_377, Bls12_377>()
+    }
+
+    // the in place contributions must find the end of keys which are not all the same size
+    fn contribute_in_place_after_identity_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let rng = &mut thread_rng();
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.contribute_with_identity(b"alice", rng).unwrap();
+        mpc.contribute(rng).unwrap();
+        let mut before = vec![];
+        mpc.write(&mut before).unwrap();
+
+        let mut buf = before.clone();
+        buf.resize(buf.len() + PublicKey::<E>::size(), 0);
+        let hash = contribute::<E, _>(&mut buf, rng, 4).unwrap();
+        let hashes = verify::<E>(&mut before.clone(), &mut buf, 4).unwrap();
+        assert_eq!(hashes.last(), Some(&hash));
+
+        let mut buf = before.clone();
+        buf.resize(buf.len() + PublicKey::<E>::size(), 0);
+        let hash = contribute_resumable::<E, _, _>(&mut buf, rng, 4, |_| Ok(())).unwrap();
+        let hashes = verify::<E>(&mut before.clone(), &mut buf, 4).unwrap();
+        assert_eq!(hashes.last(), Some(&hash));
+
+        let path = std::env::temp_dir().join(format!("phase2_contribute_after_identity_{}", std::process::id()));
+        std::fs::write(&path, &before).unwrap();
+        let hash = MPCParameters::<E>::contribute_mmap(&path, rng).unwrap();
+        let contributed = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let after = MPCParameters::<E>::read(&contributed[..]).unwrap();
+        assert_eq!(after.contributions[0].identity(), Some(&b"alice"[..]));
+        assert_eq!(mpc.verify(&after).unwrap().last(), Some(&hash));
+    }
+
+    #[test]
+    fn position_of_contribution() {
+        let hashes = [[1u8; HASH_LEN], [2u8; HASH_LEN], [3u8; HASH_LEN]];
+        assert_eq!(super::position_of_contribution(&hashes, &[1u8; HASH_LEN]), Some(0));
+        assert_eq!(super::position_of_contribution(&hashes, &[2u8; HASH_LEN]), Some(1));
+        assert_eq!(super::position_of_contribution(&hashes, &[3u8; HASH_LEN]), Some(2));
+        assert_eq!(super::position_of_contribution(&hashes, &[4u8; HASH_LEN]), None);
+        assert_eq!(super::position_of_contribution(&[], &[1u8; HASH_LEN]), None);
+    }
+
+    #[test]
+    fn check_no_duplicate_contributions() {
+        check_no_duplicate_contributions_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn check_no_duplicate_contributions_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut contribution = mpc.clone();
+        contribution.contribute(rng).unwrap();
+        contribution.contribute(rng).unwrap();
+        contribution.check_no_duplicate_contributions().unwrap();
+
+        // replay the first contribution
+        let replayed = contribution.contributions[0].clone();
+        contribution.contributions.push(replayed);
+        let err = contribution.check_no_duplicate_contributions().unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::DuplicateContribution { index: 2 })
+        ));
+
+        // the transcript check alone reports a less helpful error,
+        // which `verify` now reports after the duplicate
+        let err = verify_transcript(contribution.cs_hash, &contribution.contributions).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::BrokenInvariant(InvariantKind::Transcript))
+        ));
+        let err = mpc.verify(&contribution).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::DuplicateContribution { index: 2 })
+        ));
+    }
+
+    #[test]
+    fn ensure_unchanged_hash() {
+        let hash = [7u8; HASH_LEN];
+        super::ensure_unchanged_hash(&hash, &[7u8; HASH_LEN], InvariantKind::CsHash).unwrap();
+
+        // a difference in the last byte is detected like any other
+        let mut other = hash;
+        other[HASH_LEN - 1] = 8;
+        let err = super::ensure_unchanged_hash(&hash, &other, InvariantKind::CsHash).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::BrokenInvariant(InvariantKind::CsHash))
+        ));
+        assert!(!contains_contribution(&[hash], &other));
+        assert!(contains_contribution(&[other, hash], &hash));
+    }
+
+    #[test]
+    fn contribute_chunks_with_shared_delta() {
+        contribute_chunks_with_shared_delta_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn contribute_chunks_with_shared_delta_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.contribute(&mut thread_rng()).unwrap();
+        let seed = [5u8; 32];
+
+        let mut expected = mpc.clone();
+        expected.contribute(&mut ChaChaRng::from_seed(seed)).unwrap();
+
+        // the coordinator derives the same delta as `contribute`
+        let Keypair { private_key, .. } = Keypair::new(
+            mpc.params.delta_g1,
+            mpc.cs_hash,
+            &mpc.contributions,
+            &mut ChaChaRng::from_seed(seed),
+        );
+        let delta_inv = private_key.delta_inv();
+
+        for (query, expected_query) in &[
+            (&mpc.params.h_query, &expected.params.h_query),
+            (&mpc.params.l_query, &expected.params.l_query),
+        ] {
+            let mut serialized = vec![];
+            for element in query.iter() {
+                element.serialize(&mut serialized).unwrap();
+            }
+            for chunk in serialized.chunks_mut(3 * E::G1Affine::SERIALIZED_SIZE) {
+                contribute_chunk::<E>(chunk, &delta_inv, 2).unwrap();
+            }
+            let contributed = serialized
+                .chunks(E::G1Affine::SERIALIZED_SIZE)
+                .map(|mut bytes| E::G1Affine::deserialize(&mut bytes).unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(&contributed, *expected_query);
+        }
+
+        // chunks must contain whole elements
+        let mut partial = vec![0u8; E::G1Affine::SERIALIZED_SIZE + 1];
+        assert!(contribute_chunk::<E>(&mut partial, &delta_inv, 2).is_err());
+    }
+
+    #[test]
+    fn verify_chunk_range() {
+        verify_chunk_range_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn verify_chunk_range_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut contribution = mpc.clone();
+        contribution.contribute(rng).unwrap();
+        mpc.verify(&contribution).unwrap();
+
+        let serialize = |elements: &[E::G1Affine]| {
+            let mut serialized = vec![];
+            for element in elements {
+                element.serialize(&mut serialized).unwrap();
+            }
+            serialized
+        };
+        let verify_ranges = |after: &MPCParameters<E>| -> Result<()> {
+            for (before_query, after_query) in &[
+                (&mpc.params.h_query, &after.params.h_query),
+                (&mpc.params.l_query, &after.params.l_query),
+            ] {
+                // split the query between 3 workers
+                let len = before_query.len();
+                let ranges = vec![0..len / 3, len / 3..2 * len / 3, 2 * len / 3..len];
+                for range in &ranges {
+                    super::verify_chunk_range::<E>(
+                        &serialize(&before_query[range.clone()]),
+                        &serialize(&after_query[range.clone()]),
+                        mpc.params.vk.delta_g2,
+                        after.params.vk.delta_g2,
+                        range.clone(),
+                    )?;
+                }
+                ensure_ranges_cover(&ranges, len)?;
+            }
+            Ok(())
+        };
+        verify_ranges(&contribution).unwrap();
+
+        // the workers reject what the whole verification rejects
+        let mut corrupted = contribution.clone();
+        corrupted.corrupt_delta(rng);
+        mpc.verify(&corrupted).unwrap_err();
+        let err = verify_ranges(&corrupted).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::VerificationError(VerificationError::InvalidRatio("Query range ratio check failed"))
+        ));
+
+        // a missing range is reported
+        let err = ensure_ranges_cover(&[4..7, 0..2], 7).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::UncoveredRange { start: 2, end: 4 })
+        ));
+        assert!(ensure_ranges_cover(&[0..3], 7).is_err());
+    }
+
+    #[test]
+    fn check_cs_hash() {
+        check_cs_hash_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn check_cs_hash_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        let cs_hash = hash_params(&mpc.params).unwrap();
+        mpc.check_cs_hash(&cs_hash).unwrap();
+
+        assert_eq!(mpc.cs_hash(), &cs_hash);
+
+        // contributions do not change the cs_hash
+        mpc.contribute(&mut thread_rng()).unwrap();
+        mpc.check_cs_hash(&cs_hash).unwrap();
+        assert_eq!(mpc.cs_hash(), &cs_hash);
+
+        let err = mpc.check_cs_hash(&[0; HASH_LEN]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::BrokenInvariant(InvariantKind::CsHash))
+        ));
+    }
+
+    #[test]
+    fn validate_cs_hash() {
+        validate_cs_hash_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn validate_cs_hash_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.validate_cs_hash().unwrap();
+
+        // a hand edited proving key no longer matches the cs_hash
+        let cs_hash = mpc.cs_hash;
+        mpc.params.a_query[0] = mpc.params.a_query[1];
+        let err = mpc.validate_cs_hash().unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::BrokenInvariant(InvariantKind::CsHash))
+        ));
+
+        // until the cs_hash is recomputed
+        mpc.recompute_cs_hash().unwrap();
+        assert_ne!(mpc.cs_hash, cs_hash);
+        mpc.validate_cs_hash().unwrap();
+
+        // a corrupted cs_hash is caught when reading
+        let mut serialized = vec![];
+        mpc.write(&mut serialized).unwrap();
+        MPCParameters::<E>::read_and_validate_cs_hash(&serialized[..]).unwrap();
+        serialized[PARAMS_HEADER_SIZE + mpc.params.serialized_size()] ^= 1;
+        MPCParameters::<E>::read(&serialized[..]).unwrap();
+        let err = MPCParameters::<E>::read_and_validate_cs_hash(&serialized[..]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::BrokenInvariant(InvariantKind::CsHash))
+        ));
+
+        // contributions change the proving key, so the cs_hash cannot be recomputed
+        mpc.contribute(&mut thread_rng()).unwrap();
+        let err = mpc.validate_cs_hash().unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::CsHashNotRecomputable)));
+        let err = mpc.recompute_cs_hash().unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::CsHashNotRecomputable)));
+    }
+
+    #[test]
+    fn phase1_digest() {
+        phase1_digest_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn phase1_digest_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        // each ceremony is created from a different phase 1 output
+        let mpc = generate_ceremony::<Aleo, E>();
+        let other = generate_ceremony::<Aleo, E>();
+        assert_ne!(mpc.phase1_digest, other.phase1_digest);
+
+        // the digest survives contributions and serialization
+        let mut contributed = mpc.clone();
+        contributed.contribute(rng).unwrap();
+        assert_eq!(contributed.phase1_digest, mpc.phase1_digest);
+        let mut serialized = vec![];
+        contributed.write(&mut serialized).unwrap();
+        let deserialized = MPCParameters::<E>::read(&serialized[..]).unwrap();
+        assert_eq!(deserialized.phase1_digest, mpc.phase1_digest);
+        mpc.verify(&deserialized).unwrap();
+
+        // parameters from another phase 1 output do not verify
+        let mut other_contributed = other.clone();
+        other_contributed.contribute(rng).unwrap();
+        mpc.verify(&other_contributed).unwrap_err();
+        other.verify(&contributed).unwrap_err();
+
+        // even if only the phase 1 digest was swapped
+        let mut swapped = contributed.clone();
+        swapped.phase1_digest = other.phase1_digest;
+        let err = mpc.verify(&swapped).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::BrokenInvariant(InvariantKind::Phase1Digest))
+        ));
+    }
+
+    #[test]
+    fn read_legacy() {
+        read_legacy_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn read_legacy_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let rng = &mut thread_rng();
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.contribute(rng).unwrap();
+        mpc.contribute(rng).unwrap();
+        mpc.phase1_digest = [0u8; HASH_LEN];
+
+        // the layout from before the header, the phase 1 digest and the identities
+        let mut legacy = vec![];
+        mpc.write_proving_key_only(&mut legacy).unwrap();
+        legacy.extend_from_slice(&mpc.cs_hash);
+        legacy.extend_from_slice(&(mpc.contributions.len() as u32).to_be_bytes());
+        for pubkey in &mpc.contributions {
+            let mut serialized = vec![];
+            pubkey.write(&mut serialized).unwrap();
+            // drop the flag telling there is no identity
+            assert_eq!(serialized.pop(), Some(0));
+            legacy.extend_from_slice(&serialized);
+        }
+        let err = MPCParameters::<E>::read(&legacy[..]).unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::InvalidMagic)));
+        let read = MPCParameters::<E>::read_legacy(&legacy[..]).unwrap();
+        assert_eq!(read, mpc);
+
+        // the transcript is unchanged, so contributions on those parameters verify
+        let mut contribution = read.clone();
+        let hash = contribution.contribute(rng).unwrap();
+        assert_eq!(read.verify(&contribution).unwrap().last(), Some(&hash));
+
+        // and they migrate by being written back
+        let mut migrated = vec![];
+        read.write(&mut migrated).unwrap();
+        assert_eq!(MPCParameters::<E>::read(&migrated[..]).unwrap(), mpc);
+    }
+
+    #[test]
+    fn diff() {
+        diff_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn diff_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mpc = generate_ceremony::<Aleo, E>();
+        assert!(mpc.diff(&mpc).is_empty());
+
+        // a contribution only changes the deltas
+        let mut contribution = mpc.clone();
+        contribution.contribute(rng).unwrap();
+        assert_eq!(mpc.diff(&contribution), vec![
+            InvariantKind::DeltaG1,
+            InvariantKind::DeltaG2
+        ]);
+
+        // every other change is reported at once
+        let mut broken = contribution.clone();
+        broken.params.vk.alpha_g1 = broken.params.beta_g1;
+        broken.params.vk.gamma_abc_g1.pop();
+        broken.params.b_g2_query[0] = broken.params.b_g2_query[1];
+        broken.params.l_query.pop();
+        broken.cs_hash = [0; HASH_LEN];
+        broken.contributions.clear();
+        assert_eq!(contribution.diff(&broken), vec![
+            InvariantKind::AlphaG1,
+            InvariantKind::GammaAbcG1,
+            InvariantKind::BetaG2Query,
+            InvariantKind::LQuery,
+            InvariantKind::CsHash,
+            InvariantKind::Contributions,
+        ]);
+    }
+
+    #[test]
+    fn verify_vk_consistency() {
+        verify_vk_consistency_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn verify_vk_consistency_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let initial = generate_ceremony::<Aleo, E>();
+        let mut contribution = initial.clone();
+        contribution.contribute(rng).unwrap();
+        contribution.contribute(rng).unwrap();
+        contribution.verify_vk_consistency(&initial).unwrap();
+
+        let mut changed_ic = contribution.clone();
+        changed_ic.params.vk.gamma_abc_g1[0] = changed_ic.params.vk.gamma_abc_g1[0].mul(E::Fr::one().double());
+        let err = changed_ic.verify_vk_consistency(&initial).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::BrokenInvariantAt {
+                kind: InvariantKind::GammaAbcG1,
+                index: 0
+            })
+        ));
+
+        let mut changed_delta = contribution;
+        changed_delta.params.vk.delta_g2 = initial.params.vk.delta_g2;
+        let err = changed_delta.verify_vk_consistency(&initial).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::VerificationError(VerificationError::InvalidRatio("Inconsistent G2 Delta"))
+        ));
+    }
+
+    #[test]
+    fn contribute_with_receipt() {
+        contribute_with_receipt_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn contribute_with_receipt_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut contribution = mpc.clone();
+        contribution.contribute(rng).unwrap();
+        let receipt = contribution.contribute_with_receipt(rng).unwrap();
+        contribution.contribute(rng).unwrap();
+
+        // the receipt describes the appended public key
+        let pubkey = &contribution.contributions[1];
+        assert_eq!(receipt.contribution_index, 1);
+        assert_eq!(&receipt.hash[..], &pubkey.hash()[..]);
+        assert_eq!(receipt.delta_after, pubkey.delta_after);
+        assert_eq!(&receipt.cs_hash[..], &mpc.cs_hash[..]);
+
+        // and can be checked against the verified parameters
+        let hashes = mpc.verify(&contribution).unwrap();
+        assert!(contains_contribution(&hashes, &receipt.hash));
+        assert!(receipt.is_included_in(&contribution, &hashes));
+
+        let mut wrong_index = receipt.clone();
+        wrong_index.contribution_index = 0;
+        assert!(!wrong_index.is_included_in(&contribution, &hashes));
+        let mut wrong_delta = receipt.clone();
+        wrong_delta.delta_after = contribution.contributions[0].delta_after;
+        assert!(!wrong_delta.is_included_in(&contribution, &hashes));
+        let mut wrong_cs_hash = receipt;
+        wrong_cs_hash.cs_hash = [0; HASH_LEN];
+        assert!(!wrong_cs_hash.is_included_in(&contribution, &hashes));
+    }
+
+    #[test]
+    fn contribute_composed() {
+        contribute_composed_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn contribute_composed_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut first = ChaChaRng::from_seed([1u8; 32]);
+        let mut second = ChaChaRng::from_seed([2u8; 32]);
+        let mut third = ChaChaRng::from_seed([3u8; 32]);
+
+        // the composite delta is the product of the deltas sampled from each rng
+        let product =
+            E::Fr::rand(&mut first.clone()) * E::Fr::rand(&mut second.clone()) * E::Fr::rand(&mut third.clone());
+        let expected = mpc.params.delta_g1.mul(product);
+
+        let mut composed = mpc.clone();
+        let hash = composed
+            .contribute_composed(&mut [&mut first, &mut second, &mut third])
+            .unwrap();
+        assert_eq!(composed.params.delta_g1, expected);
+        assert_eq!(composed.contributions.len(), 1);
+        assert_eq!(composed.contributions[0].delta_after, expected);
+
+        // it verifies like any other contribution
+        assert_eq!(&mpc.verify(&composed).unwrap()[..], &[hash][..]);
+
+        let mut empty = mpc.clone();
+        assert!(matches!(
+            empty.contribute_composed(&mut []).unwrap_err(),
+            Error::Phase2Error(Phase2Error::NoRandomness)
+        ));
+    }
+
+    #[test]
+    fn contribute_from_seed() {
+        contribute_from_seed_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn contribute_from_seed_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let seed = [7u8; 32];
+
+        let mut contribution1 = mpc.clone();
+        let hash1 = contribution1.contribute_from_seed(&seed).unwrap();
+        let mut contribution2 = mpc.clone();
+        let hash2 = contribution2.contribute_from_seed(&seed).unwrap();
+        assert_eq!(
+            contribution1.contributions[0].delta_after,
+            contribution2.contributions[0].delta_after
+        );
+        assert_eq!(&hash1[..], &hash2[..]);
+
+        // same as contributing with an rng seeded the same way
+        let mut contribution3 = mpc.clone();
+        let hash3 = contribution3.contribute(&mut ChaChaRng::from_seed(seed)).unwrap();
+        assert_eq!(&hash1[..], &hash3[..]);
+        assert_eq!(contribution1, contribution3);
+
+        mpc.verify(&contribution1).unwrap();
+    }
+
+    #[test]
+    fn contribute_mmap() {
+        contribute_mmap_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn contribute_mmap_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.contribute(&mut thread_rng()).unwrap();
+        let mut before = vec![];
+        mpc.write(&mut before).unwrap();
+
+        let path = std::env::temp_dir().join(format!("phase2_contribute_mmap_{}", std::process::id()));
+        // trailing bytes, e.g. left by an interrupted upload, are not part of the transcript
+        let mut padded = before.clone();
+        padded.extend_from_slice(&[0xff; 7]);
+        std::fs::write(&path, &padded).unwrap();
+        let seed = [3u8; 32];
+        let hash = MPCParameters::<E>::contribute_mmap(&path, &mut ChaChaRng::from_seed(seed)).unwrap();
+        let contributed = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(contributed.len(), before.len() + PublicKey::<E>::size());
+
+        // identical to contributing in memory with the same randomness
+        let mut expected_mpc = mpc.clone();
+        let expected_hash = expected_mpc.contribute(&mut ChaChaRng::from_seed(seed)).unwrap();
+        let mut expected = vec![];
+        expected_mpc.write(&mut expected).unwrap();
+        assert_eq!(&hash[..], &expected_hash[..]);
+        assert_eq!(contributed, expected);
+
+        let after = MPCParameters::<E>::read(&contributed[..]).unwrap();
+        mpc.verify(&after).unwrap();
+    }
+
+    #[test]
+    fn append_contribution_to_file() {
+        append_contribution_to_file_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn append_contribution_to_file_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let rng = &mut thread_rng();
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.contribute(rng).unwrap();
+        let mut contribution = mpc.clone();
+        contribution.contribute(rng).unwrap();
+        let mut base = vec![];
+        mpc.write(&mut base).unwrap();
+        let mut contributed = vec![];
+        contribution.write(&mut contributed).unwrap();
+
+        // the full read, verify and write path
+        let after = MPCParameters::<E>::read(&contributed[..]).unwrap();
+        let expected_hashes = mpc.verify(&after).unwrap();
+        let mut expected = vec![];
+        after.write(&mut expected).unwrap();
+
+        let path = |name: &str| std::env::temp_dir().join(format!("phase2_append_{}_{}", name, std::process::id()));
+        let (base_path, contributed_path, out_path) = (path("base"), path("contributed"), path("out"));
+        std::fs::write(&base_path, &base).unwrap();
+        std::fs::write(&contributed_path, &contributed).unwrap();
+
+        let hashes =
+            crate::chunked_groth16::append_contribution_to_file::<E>(&base_path, &contributed_path, &out_path, 4)
+                .unwrap();
+        assert_eq!(hashes, expected_hashes);
+        assert_eq!(std::fs::read(&out_path).unwrap(), expected);
+        assert_eq!(std::fs::read(&base_path).unwrap(), base);
+
+        // the canonical file can be updated in place
+        crate::chunked_groth16::append_contribution_to_file::<E>(&base_path, &contributed_path, &base_path, 4).unwrap();
+        assert_eq!(std::fs::read(&base_path).unwrap(), expected);
+
+        // including when it is named by another path
+        std::fs::write(&base_path, &base).unwrap();
+        let other_base_path = std::env::temp_dir().join(".").join(base_path.file_name().unwrap());
+        crate::chunked_groth16::append_contribution_to_file::<E>(&base_path, &contributed_path, &other_base_path, 4)
+            .unwrap();
+        assert_eq!(std::fs::read(&base_path).unwrap(), expected);
+        let mut tmp_path = base_path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        assert!(!std::path::Path::new(&tmp_path).exists());
+
+        // an invalid contribution is rejected before anything is written
+        std::fs::write(&base_path, &base).unwrap();
+        std::fs::remove_file(&out_path).unwrap();
+        let mut corrupted = contribution.clone();
+        corrupted.corrupt_delta(rng);
+        let mut corrupted_serialized = vec![];
+        corrupted.write(&mut corrupted_serialized).unwrap();
+        std::fs::write(&contributed_path, &corrupted_serialized).unwrap();
+        crate::chunked_groth16::append_contribution_to_file::<E>(&base_path, &contributed_path, &out_path, 4)
+            .unwrap_err();
+        assert!(!out_path.exists());
+        assert_eq!(std::fs::read(&base_path).unwrap(), base);
+
+        std::fs::remove_file(&base_path).unwrap();
+        std::fs::remove_file(&contributed_path).unwrap();
+    }
+
+    #[test]
+    fn contribute_beacon() {
+        contribute_beacon_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn contribute_beacon_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.contribute(&mut thread_rng()).unwrap();
+        let beacon_hash = [42u8; 32];
+
+        let mut contribution = mpc.clone();
+        let hash = contribution.contribute_beacon(beacon_hash, 1 << 10).unwrap();
+        let hashes = mpc.verify(&contribution).unwrap();
+        assert!(contains_contribution(&hashes, &hash));
+
+        // the beacon contribution can be reproduced by anyone
+        let mut seed = beacon_hash;
+        for _ in 0..(1 << 10) {
+            seed.copy_from_slice(&Sha256::digest(&seed));
+        }
+        let mut recomputed = mpc.clone();
+        recomputed.contribute_from_seed(&seed).unwrap();
+        assert_eq!(recomputed, contribution);
+    }
+
+    #[test]
+    fn contribute_with_pow() {
+        contribute_with_pow_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn contribute_with_pow_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let nonce = b"round 1";
+        let mpc = generate_ceremony::<Aleo, E>();
+        assert!(mpc.verify_pow(nonce, 4, 0).is_err());
+
+        let mut contribution = mpc.clone();
+        let (hash, solution) = contribution.contribute_with_pow(&mut thread_rng(), nonce, 4).unwrap();
+        contribution.verify_pow(nonce, 4, solution).unwrap();
+        let hashes = mpc.verify(&contribution).unwrap();
+        assert!(contains_contribution(&hashes, &hash));
+
+        // a difficulty which can never be met is rejected before contributing
+        let err = contribution
+            .contribute_with_pow(&mut thread_rng(), nonce, crate::pow::MAX_POW_DIFFICULTY + 1)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::ProofOfWorkTooHard { .. })
+        ));
+        assert_eq!(contribution.contributions.len(), 1);
+    }
+
+    #[test]
+    fn verify_delta_update() {
+        verify_delta_update_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn verify_delta_update_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut contribution = mpc.clone();
+        contribution.contribute(rng).unwrap();
+        mpc.verify_delta_update(&contribution).unwrap();
+
+        // the transcript is not checked
+        let mut bad_transcript = contribution.clone();
+        bad_transcript.contributions[0].transcript = [0; HASH_LEN];
+        mpc.verify_delta_update(&bad_transcript).unwrap();
+        mpc.verify(&bad_transcript).unwrap_err();
+
+        // but the queries are
+        let mut bad_l = contribution.clone();
+        bad_l.params.l_query[0] = bad_l.params.l_query[0].mul(E::Fr::one().double());
+        mpc.verify_delta_update(&bad_l).unwrap_err();
+
+        // and so is delta
+        let mut bad_delta = contribution;
+        bad_delta.params.vk.delta_g2 = mpc.params.vk.delta_g2;
+        mpc.verify_delta_update(&bad_delta).unwrap_err();
+    }
+
+    #[test]
+    fn verify_append() {
+        verify_append_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn verify_append_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut contribution1 = mpc.clone();
+        let hash1 = contribution1.contribute(rng).unwrap();
+        let mut contribution2 = contribution1.clone();
+        let hash2 = contribution2.contribute(rng).unwrap();
+
+        assert_eq!(mpc.verify_append(&contribution1).unwrap(), hash1);
+        assert_eq!(contribution1.verify_append(&contribution2).unwrap(), hash2);
+
+        // jumping over a contribution is a valid extension, but not a valid append
+        mpc.verify(&contribution2).unwrap();
+        let err = mpc.verify_append(&contribution2).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::UnexpectedContributionCount { expected: 1, found: 2 })
+        ));
+    }
+
+    #[test]
+    fn verify_against_circuit() {
+        verify_against_circuit_curve::<Bls12_377>()
+    }
+
+    fn verify_against_circuit_curve<E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let powers = 5;
+        let batch = 16;
+        let phase2_size = 7;
+        let params = Phase1Parameters::<E>::new_full(ProvingSystem::Groth16, powers, batch);
+        let compressed = UseCompression::No;
+        let groth_params = |output: &[u8]| {
+            let accumulator = Phase1::deserialize(output, compressed, CheckForCorrectness::Full, &params).unwrap();
+            Groth16Params::<E>::new(
+                phase2_size,
+                accumulator.tau_powers_g1,
+                accumulator.tau_powers_g2,
+                accumulator.alpha_tau_powers_g1,
+                accumulator.beta_tau_powers_g1,
+                accumulator.beta_g2,
+            )
+            .unwrap()
+        };
+        let (_, output, _, _) = setup_verify(compressed, CheckForCorrectness::Full, compressed, &params);
+
+        let assembly = circuit_to_qap::<E, E, _>(TestCircuit::<E>(None)).unwrap();
+        let mut mpc = MPCParameters::new(assembly, groth_params(&output)).unwrap();
+        let hash1 = mpc.contribute(rng).unwrap();
+        let hash2 = mpc.contribute(rng).unwrap();
+
+        let hashes = mpc
+            .verify_against_circuit(TestCircuit::<E>(None), groth_params(&output))
+            .unwrap();
+        assert_eq!(hashes, vec![hash1, hash2]);
+
+        // Groth16 parameters from another Phase 1 transcript are rejected
+        let (_, other_output, _, _) = setup_verify(compressed, CheckForCorrectness::Full, compressed, &params);
+        mpc.verify_against_circuit(TestCircuit::<E>(None), groth_params(&other_output))
+            .unwrap_err();
+    }
+
+    #[test]
+    fn verify_spans() {
+        verify_spans_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn verify_spans_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        use std::sync::{Arc, Mutex};
+        use tracing::span::{Attributes, Id};
+        use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, Layer};
+
+        /// Records the name of each new span along with the name of its parent
+        struct SpanRecorder(Arc<Mutex<Vec<(&'static str, Option<&'static str>)>>>);
+
+        impl<S: tracing::Subscriber + for<'a> LookupSpan<'a>> Layer<S> for SpanRecorder {
+            fn on_new_span(&self, _: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+                let span = ctx.span(id).unwrap();
+                let parent = span.parent().map(|parent| parent.name());
+                self.0.lock().unwrap().push((span.name(), parent));
+            }
+        }
+
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut contribution = mpc.clone();
+        contribution.contribute(&mut thread_rng()).unwrap();
+
+        let spans = Arc::new(Mutex::new(vec![]));
+        let subscriber = tracing_subscriber::registry().with(SpanRecorder(spans.clone()));
+        tracing::subscriber::with_default(subscriber, || mpc.verify(&contribution).unwrap());
+
+        let spans = spans.lock().unwrap();
+        assert_eq!(spans[0], ("verify", None));
+        for &stage in &[
+            "delta",
+            "query_density",
+            "verifying_key",
+            "query_equality",
+            "query_ratios",
+            "transcript",
+        ] {
+            assert!(spans.contains(&(stage, Some("verify"))), "missing span {}", stage);
+        }
+    }
+
+    #[test]
+    fn verify_deltas_only() {
+        verify_deltas_only_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn verify_deltas_only_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut contribution = mpc.clone();
+        contribution.contribute(&mut thread_rng()).unwrap();
+        let digest = mpc.queries_digest().unwrap();
+        assert_eq!(digest, contribution.queries_digest().unwrap());
+        let expected = mpc.verify(&contribution).unwrap();
+
+        // the queries before the contribution are not needed
+        let mut without_queries = mpc.clone();
+        without_queries.params.a_query.clear();
+        without_queries.params.b_g1_query.clear();
+        without_queries.params.b_g2_query.clear();
+        assert_eq!(
+            without_queries.verify_deltas_only(&contribution, &digest).unwrap(),
+            expected
+        );
+
+        // a changed A query is caught by its digest
+        contribution.params.a_query[1] = contribution.params.a_query[0];
+        let err = mpc.verify_deltas_only(&contribution, &digest).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::QueriesDigestMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_with_rng() {
+        verify_with_rng_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn verify_with_rng_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut contribution = mpc.clone();
+        contribution.contribute(&mut thread_rng()).unwrap();
+
+        // the batching coefficients are drawn from the given RNG, so two runs
+        // seeded alike consume exactly the same randomness
+        let seed = [3u8; 32];
+        let mut rng1 = ChaChaRng::from_seed(seed);
+        let mut rng2 = ChaChaRng::from_seed(seed);
+        let hashes = mpc.verify_with_rng(&contribution, &mut rng1).unwrap();
+        assert_eq!(hashes, mpc.verify_with_rng(&contribution, &mut rng2).unwrap());
+        assert_eq!(rng1.get_word_pos(), rng2.get_word_pos());
+        assert!(rng1.get_word_pos() > ChaChaRng::from_seed(seed).get_word_pos());
+        assert_eq!(rng1.gen::<u64>(), rng2.gen::<u64>());
+
+        // any other RNG accepts the same contribution
+        assert_eq!(
+            hashes,
+            mpc.verify_with_rng(&contribution, &mut ChaChaRng::from_seed([4u8; 32]))
+                .unwrap()
+        );
+        assert_eq!(hashes, mpc.verify(&contribution).unwrap());
+
+        // a seeded run still rejects an invalid contribution
+        contribution.corrupt_delta(&mut thread_rng());
+        mpc.verify_with_rng(&contribution, &mut ChaChaRng::from_seed(seed))
+            .unwrap_err();
+    }
+
+    #[test]
+    fn batch_verify() {
+        batch_verify_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn batch_verify_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mpc1 = generate_ceremony::<Aleo, E>();
+        let mut contribution1 = mpc1.clone();
+        contribution1.contribute(rng).unwrap();
+        contribution1.contribute(rng).unwrap();
+
+        let mpc2 = generate_ceremony::<Aleo, E>();
+        let mut contribution2 = mpc2.clone();
+        contribution2.contribute(rng).unwrap();
+
+        let hashes = MPCParameters::batch_verify(&[(&mpc1, &contribution1), (&mpc2, &contribution2)]).unwrap();
+        assert_eq!(hashes, vec![
+            mpc1.verify(&contribution1).unwrap(),
+            mpc2.verify(&contribution2).unwrap()
+        ]);
+
+        // a single invalid pair makes the whole batch fail with its error
+        contribution2.corrupt_delta(rng);
+        let err = MPCParameters::batch_verify(&[(&mpc1, &contribution1), (&mpc2, &contribution2)]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::VerificationError(VerificationError::InvalidRatio("H_query ratio check failed"))
+        ));
+    }
+
+    #[test]
+    fn verified_hashes_iter() {
+        verified_hashes_iter_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn verified_hashes_iter_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        for _ in 0..3 {
+            mpc.contribute(rng).unwrap();
+        }
+
+        let hashes = mpc.verified_hashes_iter().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(hashes, verify_transcript(mpc.cs_hash, &mpc.contributions).unwrap());
+
+        // the iterator stops after the first invalid contribution
+        mpc.contributions[1].s_delta = mpc.contributions[1]
+            .s_delta
+            .mul(<E::Fr as snarkvm_utilities::UniformRand>::rand(rng));
+        let results = mpc.verified_hashes_iter().collect::<Vec<_>>();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap(), &hashes[0]);
+        assert!(results[1].is_err());
+    }
+
+    #[test]
+    fn verify_transcript_incremental() {
+        verify_transcript_incremental_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn verify_transcript_incremental_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        for _ in 0..4 {
+            mpc.contribute(rng).unwrap();
+        }
+
+        let expected = verify_transcript(mpc.cs_hash, &mpc.contributions).unwrap();
+        for already_verified in 0..=mpc.contributions.len() {
+            let hashes =
+                super::verify_transcript_incremental(mpc.cs_hash, &mpc.contributions, already_verified).unwrap();
+            assert_eq!(hashes, expected);
+        }
+        super::verify_transcript_incremental(mpc.cs_hash, &mpc.contributions, 5).unwrap_err();
+
+        // a broken contribution after the verified ones is caught
+        let mut broken = mpc.contributions.clone();
+        broken[3].delta_after = broken[2].delta_after;
+        super::verify_transcript_incremental(mpc.cs_hash, &broken, 3).unwrap_err();
+    }
+
+    #[test]
+    fn verify_transcript_batched() {
+        verify_transcript_batched_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn verify_transcript_batched_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        for _ in 0..3 {
+            mpc.contribute(rng).unwrap();
+        }
+
+        // the batched and the per-step checks agree on a valid transcript
+        let per_step = verify_transcript_with_audit(mpc.cs_hash, &mpc.contributions).unwrap();
+        let batched = verify_transcript(mpc.cs_hash, &mpc.contributions).unwrap();
+        assert_eq!(
+            batched,
+            per_step.into_iter().map(|entry| entry.hash).collect::<Vec<_>>()
+        );
+
+        // ...and report the same error on broken ones
+        let mut broken_delta = mpc.contributions.clone();
+        broken_delta[2].delta_after = broken_delta[1].delta_after;
+        let mut broken_signature = mpc.contributions.clone();
+        broken_signature[2].r_delta = broken_signature[1].r_delta;
+        let mut broken_transcript = mpc.contributions.clone();
+        broken_transcript[0].delta_after = broken_transcript[1].delta_after;
+        for broken in &[broken_delta, broken_signature, broken_transcript] {
+            let per_step = verify_transcript_with_audit(mpc.cs_hash, broken).unwrap_err();
+            let batched = verify_transcript(mpc.cs_hash, broken).unwrap_err();
+            assert_eq!(batched.to_string(), per_step.to_string());
+        }
+
+        // with several broken contributions, both report the first one: here the
+        // delta of the second contribution, before the signature of the fourth
+        let mut broken = generate_ceremony::<Aleo, E>();
+        broken.contribute(rng).unwrap();
+        broken.contribute(rng).unwrap();
+        broken.corrupt_delta(rng);
+        broken.contribute(rng).unwrap();
+        broken.contribute(rng).unwrap();
+        broken.contributions[3].r_delta = broken.contributions[2].r_delta;
+        for err in &[
+            verify_transcript_with_audit(broken.cs_hash, &broken.contributions).unwrap_err(),
+            verify_transcript(broken.cs_hash, &broken.contributions).unwrap_err(),
+        ] {
+            assert!(matches!(
+                err,
+                Error::VerificationError(VerificationError::InvalidRatio("Inconsistent G1 Delta"))
+            ));
+        }
+    }
+
+    #[test]
+    fn verify_chain() {
+        verify_chain_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn verify_chain_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mut steps = vec![generate_ceremony::<Aleo, E>()];
+        let mut expected = vec![];
+        for _ in 0..3 {
+            let mut next = steps[steps.len() - 1].clone();
+            expected.push(next.contribute(rng).unwrap());
+            steps.push(next);
+        }
+
+        let hashes = MPCParameters::verify_chain(&steps).unwrap();
+        assert_eq!(hashes, expected);
+
+        // skipping a step breaks the chain
+        let skipped = vec![steps[0].clone(), steps[1].clone(), steps[3].clone()];
+        let err = MPCParameters::verify_chain(&skipped).unwrap_err();
+        match err {
+            Error::Phase2Error(Phase2Error::BrokenChain { index: 2, cause }) => assert!(matches!(
+                *cause,
+                Error::Phase2Error(Phase2Error::UnexpectedContributionCount { expected: 2, found: 3 })
+            )),
+            err => panic!("unexpected error: {}", err),
+        }
+
+        // so does an invalid contribution
+        let mut broken = steps.clone();
+        broken[2].params.delta_g1 = broken[1].params.delta_g1;
+        let err = MPCParameters::verify_chain(&broken).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::BrokenChain { index: 2, .. })
+        ));
+
+        assert!(MPCParameters::verify_chain(&steps[..1]).is_err());
+    }
+
+    #[test]
+    fn export_transcript_json() {
+        export_transcript_json_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn export_transcript_json_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        let hashes = (0..3).map(|_| mpc.contribute(rng).unwrap()).collect::<Vec<_>>();
+
+        let json = mpc.export_transcript_json();
+        let entries: Vec<TranscriptEntry> = serde_json::from_str(&json).unwrap();
+        assert_eq!(entries.len(), 3);
+        for (i, (entry, hash)) in entries.iter().zip(&hashes).enumerate() {
+            assert_eq!(entry.index, i);
+            assert_eq!(entry.hash, hex::encode(&hash[..]));
+            let delta_after = hex::decode(&entry.delta_after).unwrap();
+            let delta_after = E::G1Affine::deserialize(&mut &delta_after[..]).unwrap();
+            assert_eq!(delta_after, mpc.contributions[i].delta_after);
+        }
+    }
+
+    #[test]
+    fn attestation() {
+        attestation_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn attestation_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut contribution = mpc.clone();
+        let hash1 = contribution.contribute(rng).unwrap();
+        let hash2 = contribution.contribute(rng).unwrap();
+
+        let hashes = mpc.verify(&contribution).unwrap();
+        let attestation = contribution.attestation(&hashes).unwrap();
+        assert_eq!(attestation.cs_hash, hex::encode(&contribution.cs_hash[..]));
+        assert_eq!(attestation.num_contributions, 2);
+        assert_eq!(attestation.contributions, vec![
+            hex::encode(&hash1[..]),
+            hex::encode(&hash2[..])
+        ]);
+
+        // a different set of contributions ends with a different delta
+        let mut other = mpc.clone();
+        other.contribute(rng).unwrap();
+        other.contribute(rng).unwrap();
+        let other_hashes = mpc.verify(&other).unwrap();
+        let other_attestation = other.attestation(&other_hashes).unwrap();
+        assert_ne!(attestation.vk_hash, other_attestation.vk_hash);
+        assert_ne!(attestation.delta_commitment, other_attestation.delta_commitment);
+
+        // the hashes must be the ones of the transcript
+        let err = contribution.attestation(&other_hashes).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::BrokenInvariantAt {
+                kind: InvariantKind::Transcript,
+                index: 0
+            })
+        ));
+        let err = contribution.attestation(&hashes[..1]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::UnexpectedContributionCount { expected: 2, found: 1 })
+        ));
+
+        let json = serde_json::to_string(&attestation).unwrap();
+        let deserialized: CeremonyAttestation = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, attestation);
+    }
+
+    #[test]
+    fn contribute_with_backend() {
+        contribute_with_backend_curve::<Bls12_377, Bls12_377>()
+    }
+
+    // a backend which keeps track of the number of points it multiplied
+    struct CountingBackend(std::cell::Cell<usize>);
+
+    impl<C: AffineCurve> ScalarMulBackend<C> for CountingBackend {
+        fn batch_mul(&self, bases: &mut [C], coeff: &C::ScalarField) -> Result<()> {
+            self.0.set(self.0.get() + bases.len());
+            CpuBackend.batch_mul(bases, coeff)
+        }
+    }
+
+    fn contribute_with_backend_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let backend = CountingBackend(Default::default());
+        let mut contribution = mpc.clone();
+        // backends can be used as trait objects
+        let dyn_backend: &dyn ScalarMulBackend<E::G1Affine> = &backend;
+        contribution
+            .contribute_with_backend(&mut thread_rng(), dyn_backend)
+            .unwrap();
+
+        assert_eq!(backend.0.get(), mpc.params.h_query.len() + mpc.params.l_query.len());
+        mpc.verify(&contribution).unwrap();
+    }
+
+    #[test]
+    fn read_checked() {
+        read_checked_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn read_checked_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        mpc.contribute(&mut thread_rng()).unwrap();
+        let mut serialized = vec![];
+        mpc.write(&mut serialized).unwrap();
+        for &check in &[
+            CheckForCorrectness::Full,
+            CheckForCorrectness::OnlyNonZero,
+            CheckForCorrectness::OnlyInGroup,
+            CheckForCorrectness::No,
+        ] {
+            let deserialized = MPCParameters::<E>::read_checked(&serialized[..], check).unwrap();
+            assert_eq!(deserialized, mpc);
+        }
+
+        let mut zeroed = mpc;
+        zeroed.params.l_query[0] = E::G1Affine::zero();
+        let mut serialized = vec![];
+        zeroed.write(&mut serialized).unwrap();
+        let err = MPCParameters::<E>::read_checked(&serialized[..], CheckForCorrectness::OnlyNonZero).unwrap_err();
+        assert!(matches!(err, Error::PointAtInfinity));
+        MPCParameters::<E>::read_checked(&serialized[..], CheckForCorrectness::No).unwrap();
+    }
+
+    #[test]
+    fn verify_reports_changed_query_index() {
+        verify_reports_changed_query_index_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn verify_reports_changed_query_index_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut before = vec![];
+        mpc.write(&mut before).unwrap();
+        let mut contribution = mpc.clone();
+        contribution.contribute(&mut thread_rng()).unwrap();
+
+        let index = contribution.params.a_query.len() - 1;
+        contribution.params.a_query[index] = E::G1Affine::prime_subgroup_generator().mul(E::Fr::one().double());
+        let err = mpc.verify(&contribution).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::BrokenInvariantAt {
+                kind: InvariantKind::AlphaG1Query,
+                index: i
+            }) if i == index
+        ));
+
+        // the index is not relative to the batch in the chunked verifier
+        let mut after = vec![];
+        contribution.write(&mut after).unwrap();
+        let err = verify::<E>(&mut before, &mut after, 2).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::BrokenInvariantAt {
+                kind: InvariantKind::AlphaG1Query,
+                index: i
+            }) if i == index
+        ));
+    }
+
+    #[test]
+    fn verify_zeroed_query_element_fails() {
+        verify_zeroed_query_element_fails_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn verify_zeroed_query_element_fails_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut before = vec![];
+        mpc.write(&mut before).unwrap();
+        let mut contribution = mpc.clone();
+        contribution.contribute(&mut thread_rng()).unwrap();
+
+        let mut zeroed_h = contribution.clone();
+        zeroed_h.params.h_query[0] = E::G1Affine::zero();
+        let mut zeroed_l = contribution;
+        zeroed_l.params.l_query[0] = E::G1Affine::zero();
+
+        for zeroed in &[zeroed_h, zeroed_l] {
+            let err = mpc.verify(zeroed).unwrap_err();
+            assert!(matches!(err, Error::PointAtInfinity));
+
+            let mut after = vec![];
+            zeroed.write(&mut after).unwrap();
+            let err = verify::<E>(&mut before.clone(), &mut after, 4).unwrap_err();
+            assert!(matches!(err, Error::PointAtInfinity));
+        }
+    }
+
+    #[test]
+    fn verify_with_self_fails() {
+        verify_with_self_fails_curve::<Bls12_377, Bls12_377>()
+    }
+
+    // if there has been no contribution
+    // then checking with itself should fail
+    fn verify_with_self_fails_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let err = mpc.verify(&mpc);
+        // we handle the error like this because [u8; HASH_LEN] does not implement
+        // debug, meaning we cannot call `assert` on it
+        if let Err(e) = err {
+            assert_eq!(e.to_string(), "Phase 2 Error: There were no contributions found");
+        } else {
+            panic!("Verifying with self must fail")
+        }
+    }
+
+    #[test]
+    fn verify_with_audit() {
+        verify_with_audit_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn verify_with_audit_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut contribution = mpc.clone();
+        contribution.contribute(rng).unwrap();
+        contribution.contribute(rng).unwrap();
+
+        let audit = mpc.verify_with_audit(&contribution).unwrap();
+        let hashes = mpc.verify(&contribution).unwrap();
+        assert_eq!(audit.len(), 2);
+
+        // the logged pairings can be replayed from the public keys alone
+        let mut old_delta = E::G1Affine::prime_subgroup_generator();
+        for (entry, (pubkey, hash)) in audit.iter().zip(contribution.contributions.iter().zip(&hashes)) {
+            assert_eq!(&entry.hash[..], &hash[..]);
+            assert_eq!(entry.delta_pairing, E::pairing(old_delta, pubkey.r_delta));
+            old_delta = pubkey.delta_after;
+        }
+
+        // `verify` batches the transcript checks, but reports the same errors
+        contribution.contributions[1].r_delta = contribution.contributions[0].r_delta;
+        assert_eq!(
+            mpc.verify(&contribution).unwrap_err().to_string(),
+            mpc.verify_with_audit(&contribution).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn verify_transcript_matches_serial() {
+        verify_transcript_matches_serial_curve::<Bls12_377, Bls12_377>()
+    }
+
+    fn verify_transcript_matches_serial_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        for _ in 0..10 {
+            mpc.contribute(rng).unwrap();
+        }
+
+        // check each contribution one after the other
+        let mut expected = vec![];
+        let mut old_delta = E::G1Affine::prime_subgroup_generator();
+        for (i, pubkey) in mpc.contributions.iter().enumerate() {
+            let hash = hash_cs_pubkeys(
+                mpc.cs_hash,
+                &mpc.contributions[0..i],
+                pubkey.s,
+                pubkey.s_delta,
+                pubkey.identity(),
+            );
+            assert_eq!(&pubkey.transcript[..], &hash[..]);
+            let r = hash_to_curve::<E::G2Affine>(&hex::encode(hash.as_ref())).0;
+            check_same_ratio::<E>(&(pubkey.s, pubkey.s_delta), &(r, pubkey.r_delta), "").unwrap();
+            check_same_ratio::<E>(&(old_delta, pubkey.delta_after), &(r, pubkey.r_delta), "").unwrap();
+            old_delta = pubkey.delta_after;
+            expected.push(pubkey.hash());
+        }
+
+        let hashes = verify_transcript(mpc.cs_hash, &mpc.contributions).unwrap();
+        assert_eq!(hashes, expected);
+
+        // a broken link in the middle of the chain is still caught
+        let mut contributions = mpc.contributions.clone();
+        contributions[5].delta_after = contributions[4].delta_after;
+        assert!(verify_transcript(mpc.cs_hash, &contributions).is_err());
+    }
+
     #[test]
     fn verify_contribution() {
-        verify_curve::<Bls12_377, Bls12_377>()
+        verify_curve::<Bls12_377, Bls12_377>();
+        verify_curve::<BW6_761, BW6_761>();
     }
 
     // contributing once and comparing with the previous step passes
     fn verify_curve<Aleo: PairingEngine, E: PairingEngine>() {
-        Subscriber::builder()
+        // the subscriber may already be set by a previous instantiation
+        let _ = Subscriber::builder()
             .with_target(false)
             .with_env_filter(EnvFilter::from_default_env())
-            .init();
+            .try_init();
 
         let rng = &mut thread_rng();
         // original
@@ -28,21 +28,31 @@ pub fn eval<E: PairingEngine>(
     Vec<E::G1Affine>,
     Vec<E::G1Affine>,
 ) {
-    // calculate the evaluated polynomials
-    let a_g1 = dot_product_vec(at, coeffs_g1, num_inputs);
-    let b_g1 = dot_product_vec(bt, coeffs_g1, num_inputs);
-    let b_g2 = dot_product_vec(bt, coeffs_g2, num_inputs);
-    let ext = dot_product_ext::<E>((at, beta_coeffs_g1), (bt, alpha_coeffs_g1), (ct, coeffs_g1), num_inputs);
+    // calculate the evaluated polynomials, which are independent of each other
+    let ((a_g1, b_g1), (b_g2, ext)) = rayon::join(
+        || {
+            rayon::join(
+                || dot_product_vec(at, coeffs_g1, num_inputs),
+                || dot_product_vec(bt, coeffs_g1, num_inputs),
+            )
+        },
+        || {
+            rayon::join(
+                || dot_product_vec(bt, coeffs_g2, num_inputs),
+                || dot_product_ext::<E>((at, beta_coeffs_g1), (bt, alpha_coeffs_g1), (ct, coeffs_g1), num_inputs),
+            )
+        },
+    );
 
     // break to `gamma_abc_g1` and `l` coeffs
     let (gamma_abc_g1, l) = ext.split_at(num_inputs);
 
     // back to affine and return
-    let a_g1 = a_g1.iter().map(|p| p.into_affine()).collect();
-    let b_g1 = b_g1.iter().map(|p| p.into_affine()).collect();
-    let b_g2 = b_g2.iter().map(|p| p.into_affine()).collect();
-    let gamma_abc_g1 = gamma_abc_g1.iter().map(|p| p.into_affine()).collect();
-    let l = l.iter().map(|p| p.into_affine()).collect();
+    let a_g1 = a_g1.par_iter().map(|p| p.into_affine()).collect();
+    let b_g1 = b_g1.par_iter().map(|p| p.into_affine()).collect();
+    let b_g2 = b_g2.par_iter().map(|p| p.into_affine()).collect();
+    let gamma_abc_g1 = gamma_abc_g1.par_iter().map(|p| p.into_affine()).collect();
+    let l = l.par_iter().map(|p| p.into_affine()).collect();
 
     (a_g1, b_g1, b_g2, gamma_abc_g1, l)
 }
@@ -110,7 +120,7 @@ fn dot_product<C: AffineCurve>(input: &[(C::ScalarField, Index)], coeffs: &[C],
 mod tests {
     use super::*;
     use phase1::helpers::testing::random_point_vec;
-    use snarkvm_curves::bls12_377::{Bls12_377, Fr, G1Affine, G1Projective};
+    use snarkvm_curves::bls12_377::{Bls12_377, Fr, G1Affine, G1Projective, G2Affine};
     use snarkvm_utilities::UniformRand;
 
     use rand::{thread_rng, Rng};
@@ -197,4 +207,43 @@ mod tests {
         }
         assert_eq!(got, expected);
     }
+
+    #[test]
+    fn test_eval() {
+        let mut rng = thread_rng();
+        let num_inputs = 3;
+        let at = (0..10).map(|_| gen_input(&mut rng)).collect::<Vec<_>>();
+        let bt = (0..10).map(|_| gen_input(&mut rng)).collect::<Vec<_>>();
+        let ct = (0..10).map(|_| gen_input(&mut rng)).collect::<Vec<_>>();
+        let coeffs_g1: Vec<G1Affine> = random_point_vec(6, &mut rng);
+        let coeffs_g2: Vec<G2Affine> = random_point_vec(6, &mut rng);
+        let alpha_coeffs_g1: Vec<G1Affine> = random_point_vec(6, &mut rng);
+        let beta_coeffs_g1: Vec<G1Affine> = random_point_vec(6, &mut rng);
+
+        let (a_g1, b_g1, b_g2, gamma_abc_g1, l) = eval::<Bls12_377>(
+            &coeffs_g1,
+            &coeffs_g2,
+            &alpha_coeffs_g1,
+            &beta_coeffs_g1,
+            &at,
+            &bt,
+            &ct,
+            num_inputs,
+        );
+
+        // it should match evaluating each query one after the other
+        let to_affine = |v: Vec<G1Projective>| v.iter().map(|p| p.into_affine()).collect::<Vec<_>>();
+        assert_eq!(a_g1, to_affine(dot_product_vec(&at, &coeffs_g1, num_inputs)));
+        assert_eq!(b_g1, to_affine(dot_product_vec(&bt, &coeffs_g1, num_inputs)));
+        let expected_b_g2 = dot_product_vec(&bt, &coeffs_g2, num_inputs);
+        assert_eq!(b_g2, expected_b_g2.iter().map(|p| p.into_affine()).collect::<Vec<_>>());
+        let ext = to_affine(dot_product_ext::<Bls12_377>(
+            (&at, &beta_coeffs_g1),
+            (&bt, &alpha_coeffs_g1),
+            (&ct, &coeffs_g1),
+            num_inputs,
+        ));
+        assert_eq!(gamma_abc_g1, &ext[..num_inputs]);
+        assert_eq!(l, &ext[num_inputs..]);
+    }
 }
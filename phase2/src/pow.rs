@@ -0,0 +1,113 @@
+//! # Proof of Work
+//!
+//! A public coordinator can ask contributors for a proof of work over their contribution's
+//! hash, which is cheap to check before running the expensive `MPCParameters::verify`.
+//! The coordinator issues a `nonce` along with the parameters, and the contributor must find
+//! a `solution` such that `BLAKE2b(contribution_hash | nonce | solution)` starts with at least
+//! `difficulty` zero bits.
+use setup_utils::{HashWriter, Phase2Error, Result, HASH_LEN};
+
+use std::io::{self, Write};
+
+/// The largest difficulty a proof of work can have, i.e. the number of bits of its hash.
+/// Any higher difficulty can never be met.
+pub const MAX_POW_DIFFICULTY: u32 = 8 * HASH_LEN as u32;
+
+/// Finds a solution to the proof of work for the given contribution hash and nonce.
+/// The expected number of hashes is `2^difficulty`, so the difficulty set by the
+/// coordinator should be kept well below `MAX_POW_DIFFICULTY`, above which this
+/// returns an error.
+pub fn solve_pow(contribution_hash: &[u8; HASH_LEN], nonce: &[u8], difficulty: u32) -> Result<u64> {
+    ensure_pow_difficulty(difficulty)?;
+    (0..)
+        .find(|&solution| leading_zero_bits(&pow_hash(contribution_hash, nonce, solution)) >= difficulty)
+        .ok_or_else(|| Phase2Error::InvalidProofOfWork.into())
+}
+
+/// Checks the proof of work for the given contribution hash and nonce.
+pub fn verify_pow(contribution_hash: &[u8; HASH_LEN], nonce: &[u8], difficulty: u32, solution: u64) -> Result<()> {
+    ensure_pow_difficulty(difficulty)?;
+    if leading_zero_bits(&pow_hash(contribution_hash, nonce, solution)) < difficulty {
+        return Err(Phase2Error::InvalidProofOfWork.into());
+    }
+    Ok(())
+}
+
+/// Checks that `difficulty` is at most `MAX_POW_DIFFICULTY`
+pub(crate) fn ensure_pow_difficulty(difficulty: u32) -> Result<()> {
+    if difficulty > MAX_POW_DIFFICULTY {
+        return Err(Phase2Error::ProofOfWorkTooHard {
+            difficulty,
+            max: MAX_POW_DIFFICULTY,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+fn pow_hash(contribution_hash: &[u8; HASH_LEN], nonce: &[u8], solution: u64) -> [u8; HASH_LEN] {
+    let mut sink = HashWriter::new(io::sink());
+    sink.write_all(&contribution_hash[..]).unwrap();
+    sink.write_all(nonce).unwrap();
+    sink.write_all(&solution.to_le_bytes()).unwrap();
+    sink.into_digest()
+}
+
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut zeros = 0;
+    for byte in hash {
+        zeros += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    zeros
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use setup_utils::Error;
+
+    #[test]
+    fn solve_and_verify() {
+        let contribution_hash = [7; HASH_LEN];
+        let nonce = b"coordinator nonce";
+        let difficulty = 8;
+
+        let solution = solve_pow(&contribution_hash, nonce, difficulty).unwrap();
+        verify_pow(&contribution_hash, nonce, difficulty, solution).unwrap();
+        // easier difficulties are also satisfied
+        verify_pow(&contribution_hash, nonce, difficulty - 1, solution).unwrap();
+
+        // find a solution which does not satisfy the difficulty
+        let invalid = (0..)
+            .find(|&s| leading_zero_bits(&pow_hash(&contribution_hash, nonce, s)) < difficulty)
+            .unwrap();
+        let err = verify_pow(&contribution_hash, nonce, difficulty, invalid).unwrap_err();
+        assert_eq!(err.to_string(), "Phase 2 Error: The proof of work is invalid");
+    }
+
+    #[test]
+    fn difficulty_too_high() {
+        let contribution_hash = [7; HASH_LEN];
+        let nonce = b"coordinator nonce";
+        for err in &[
+            solve_pow(&contribution_hash, nonce, MAX_POW_DIFFICULTY + 1).unwrap_err(),
+            verify_pow(&contribution_hash, nonce, MAX_POW_DIFFICULTY + 1, 0).unwrap_err(),
+        ] {
+            assert!(matches!(
+                err,
+                Error::Phase2Error(Phase2Error::ProofOfWorkTooHard { difficulty, max })
+                    if *difficulty == MAX_POW_DIFFICULTY + 1 && *max == MAX_POW_DIFFICULTY
+            ));
+        }
+    }
+
+    #[test]
+    fn leading_zeros() {
+        assert_eq!(leading_zero_bits(&[0, 0, 0b0001_0000, 0xff]), 19);
+        assert_eq!(leading_zero_bits(&[0xff, 0]), 0);
+        assert_eq!(leading_zero_bits(&[0, 0]), 16);
+    }
+}
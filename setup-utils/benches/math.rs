@@ -1,5 +1,5 @@
 use phase1::helpers::testing::random_point_vec;
-use setup_utils::{batch_exp, dense_multiexp, generate_powers_of_tau};
+use setup_utils::{batch_exp, batch_mul, batch_mul_parallel, check_subgroup, dense_multiexp, generate_powers_of_tau};
 
 use snarkvm_curves::{
     bls12_377::{Bls12_377, G1Affine},
@@ -87,9 +87,48 @@ fn benchmark_multiexp(c: &mut Criterion) {
     }
 }
 
+// Benchmark for the subgroup check of large vectors
+fn benchmark_check_subgroup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("SubgroupCheck");
+    group.sample_size(10);
+    let mut rng = rand::thread_rng();
+    for len in (5..12).map(|i| 2u32.pow(i)) {
+        group.throughput(Throughput::Elements(len as u64));
+        let elements: Vec<G1Affine> = random_point_vec(len as usize, &mut rng);
+
+        group.bench_with_input("check_subgroup", &len, |b, _len| {
+            b.iter(|| check_subgroup(&elements).unwrap())
+        });
+    }
+}
+
+// Benchmark comparing batch_mul with its chunked parallel version
+fn benchmark_batch_mul(c: &mut Criterion) {
+    let mut group = c.benchmark_group("BatchMul");
+    group.sample_size(10);
+    let mut rng = rand::thread_rng();
+    let len = 1 << 20;
+    group.throughput(Throughput::Elements(len as u64));
+    let mut elements: Vec<G1Affine> = random_point_vec(len, &mut rng);
+    let coeff = <Bls12_377 as PairingEngine>::Fr::rand(&mut rng);
+
+    group.bench_function("batch_mul", |b| b.iter(|| batch_mul(&mut elements, &coeff).unwrap()));
+    group.bench_function("batch_mul_parallel", |b| {
+        b.iter(|| batch_mul_parallel(&mut elements, &coeff, 1 << 12).unwrap())
+    });
+    group.finish();
+}
+
 fn randomness<G: AffineCurve>(v: &[G], rng: &mut impl Rng) -> Vec<<G::ScalarField as PrimeField>::BigInteger> {
     (0..v.len()).map(|_| G::ScalarField::rand(rng).to_repr()).collect()
 }
 
-criterion_group!(benches, benchmark_phase1, benchmark_batchexp, benchmark_multiexp);
+criterion_group!(
+    benches,
+    benchmark_phase1,
+    benchmark_batchexp,
+    benchmark_multiexp,
+    benchmark_check_subgroup,
+    benchmark_batch_mul
+);
 criterion_main!(benches);
@@ -0,0 +1,26 @@
+use crate::{batch_mul_parallel, Result};
+
+use snarkvm_curves::AffineCurve;
+
+/// A backend which multiplies a vector of points by the same scalar. This is the
+/// bulk of the work of a Phase 2 contribution, so contributors with specialized
+/// hardware (e.g. GPUs) can implement it to offload the computation. The trait
+/// is generic over the group rather than the method, so that a backend can be
+/// used as a trait object, e.g. one picked at runtime.
+pub trait ScalarMulBackend<C: AffineCurve> {
+    /// Multiplies all the `bases` by `coeff` in place
+    fn batch_mul(&self, bases: &mut [C], coeff: &C::ScalarField) -> Result<()>;
+}
+
+/// The minimum number of points multiplied on each thread by the `CpuBackend`
+const CPU_BACKEND_MIN_LEN: usize = 1 << 12;
+
+/// The default backend, which performs the multiplications on the CPU
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CpuBackend;
+
+impl<C: AffineCurve> ScalarMulBackend<C> for CpuBackend {
+    fn batch_mul(&self, bases: &mut [C], coeff: &C::ScalarField) -> Result<()> {
+        batch_mul_parallel(bases, coeff, CPU_BACKEND_MIN_LEN)
+    }
+}
@@ -41,28 +41,94 @@ impl From<Box<dyn std::any::Any + Send>> for Error {
     }
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error)]
 pub enum Phase2Error {
     #[error("Parameter should not change: {0}")]
     BrokenInvariant(InvariantKind),
+    #[error("Element {index} of {kind} should not change")]
+    BrokenInvariantAt { kind: InvariantKind, index: usize },
     #[error("Length should not change")]
     InvalidLength,
     #[error("There were no contributions found")]
     NoContributions,
     #[error("The Transcript was not consistent")]
     InvalidTranscript,
+    #[error("Cannot contribute to parameters with empty H and L queries")]
+    EmptyQueries,
+    #[error("The proof of work is invalid")]
+    InvalidProofOfWork,
+    #[error("The proof of work difficulty {difficulty} is above the maximum of {max}")]
+    ProofOfWorkTooHard { difficulty: u32, max: u32 },
+    #[error("The digest of the file does not match: expected {expected}, got {got}")]
+    DigestMismatch { expected: String, got: String },
+    #[error("Expected {expected} contributions, found {found}")]
+    UnexpectedContributionCount { expected: usize, found: usize },
+    #[error("Step {index} of the chain is not a valid contribution to the previous step: {cause}")]
+    BrokenChain {
+        index: usize,
+        #[source]
+        cause: Box<Error>,
+    },
+    #[error("Failed reading {context}: {cause}")]
+    Io {
+        context: &'static str,
+        #[source]
+        cause: io::Error,
+    },
+    #[error("The checksum of the file does not match: expected {expected}, got {got}")]
+    ChecksumMismatch { expected: String, got: String },
+    #[error("The circuit needs {needed} coefficients, but the phase 2 size is only {got}")]
+    PhaseSizeTooSmall { needed: usize, got: usize },
+    #[error("Contribution {index} has the same delta as a previous contribution")]
+    DuplicateContribution { index: usize },
+    #[error("Elements {start} to {end} of the query were not verified")]
+    UncoveredRange { start: usize, end: usize },
+    #[error("Element {index} of the L query is the point at infinity, the circuit has an unconstrained variable")]
+    UnconstrainedVariable { index: usize },
+    #[error("The digest of the A and B queries does not match: expected {expected}, got {got}")]
+    QueriesDigestMismatch { expected: String, got: String },
+    #[error("The cs_hash of parameters with contributions cannot be recomputed from their proving key")]
+    CsHashNotRecomputable,
+    #[error("The parameters do not start with the expected magic bytes, files from before the header must be read with `MPCParameters::read_legacy`")]
+    InvalidMagic,
+    #[error("Unsupported format version: expected {expected}, got {got}")]
+    UnsupportedFormatVersion { expected: u8, got: u8 },
+    #[error("The parameters are for another curve: expected {expected}, got {got}")]
+    WrongCurve { expected: String, got: String },
+    #[error("A composed contribution needs at least one source of randomness")]
+    NoRandomness,
+    #[error("The delta of a contribution cannot be zero")]
+    ZeroDelta,
+    #[error("A proof created with the proving key does not verify against its verifying key")]
+    UnusableProvingKey,
+    #[error("A circuit with {num_public} public variables cannot have only {num_variables} variables")]
+    InvalidDimensions { num_public: usize, num_variables: usize },
+    #[error("The size of the parameters does not fit in a usize")]
+    SizeOverflow,
+    #[error("The input is larger than the limit of {max_len} bytes")]
+    InputTooLarge { max_len: usize },
+    #[error("Expected {expected} elements in {field}, got {got}")]
+    UnexpectedLength {
+        field: &'static str,
+        expected: usize,
+        got: usize,
+    },
 }
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum InvariantKind {
     Contributions,
     CsHash,
+    Phase1Digest,
     AlphaG1,
     BetaG1,
     BetaG2,
     GammaAbcG1,
     GammaG2,
     DeltaG1,
+    DeltaG2,
+    HQuery,
+    LQuery,
     Transcript,
     AlphaG1Query,
     BetaG1Query,
@@ -75,12 +141,16 @@ impl fmt::Display for InvariantKind {
         match *self {
             InvariantKind::Contributions => write!(f, "Contributions"),
             InvariantKind::CsHash => write!(f, "CsHash"),
+            InvariantKind::Phase1Digest => write!(f, "Phase1Digest"),
             InvariantKind::AlphaG1 => write!(f, "AlphaG1"),
             InvariantKind::BetaG1 => write!(f, "BetaG1"),
             InvariantKind::BetaG2 => write!(f, "BetaG2"),
             InvariantKind::GammaAbcG1 => write!(f, "GammaAbcG1"),
             InvariantKind::GammaG2 => write!(f, "GammaG2"),
             InvariantKind::DeltaG1 => write!(f, "DeltaG1"),
+            InvariantKind::DeltaG2 => write!(f, "DeltaG2"),
+            InvariantKind::HQuery => write!(f, "HQuery"),
+            InvariantKind::LQuery => write!(f, "LQuery"),
             InvariantKind::Transcript => write!(f, "Transcript"),
             InvariantKind::AlphaG1Query => write!(f, "AlphaG1Query"),
             InvariantKind::BetaG1Query => write!(f, "BetaG1Query"),
@@ -1,6 +1,6 @@
 /// Utilities to read/write and convert the Powers of Tau from Phase 1
 /// to Phase 2-compatible Lagrange Coefficients.
-use crate::{buffer_size, CheckForCorrectness, Deserializer, Result, Serializer, UseCompression};
+use crate::{buffer_size, CheckForCorrectness, Deserializer, HashWriter, Result, Serializer, UseCompression, HASH_LEN};
 
 use snarkvm_algorithms::{
     cfg_into_iter,
@@ -12,7 +12,10 @@ use snarkvm_fields::PrimeField;
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
-use std::{fmt::Debug, io::Write};
+use std::{
+    fmt::Debug,
+    io::{self, Write},
+};
 use tracing::{debug, info, info_span};
 
 #[derive(Debug)]
@@ -161,6 +164,14 @@ impl<E: PairingEngine> Groth16Params<E> {
         Ok(())
     }
 
+    /// Returns the BLAKE2b hash of the uncompressed serialization of these
+    /// parameters, identifying the phase 1 output they were prepared from.
+    pub fn digest(&self) -> Result<[u8; HASH_LEN]> {
+        let mut sink = HashWriter::new(io::sink());
+        self.write(&mut sink, UseCompression::No)?;
+        Ok(sink.into_digest())
+    }
+
     /// Reads the first `num_constraints` coefficients from the provided processed
     /// Phase 1 transcript with size `phase1_size`.
     pub fn read(
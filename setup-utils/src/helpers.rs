@@ -4,9 +4,15 @@ use crate::{
 };
 
 use snarkvm_algorithms::{cfg_into_iter, cfg_iter, cfg_iter_mut};
-use snarkvm_curves::{AffineCurve, Group, PairingEngine, ProjectiveCurve};
-use snarkvm_fields::{Field, One, PrimeField, Zero};
-use snarkvm_utilities::{biginteger::BigInteger, rand::UniformRand, CanonicalSerialize, ConstantSerializedSize};
+use snarkvm_curves::{AffineCurve, Group, PairingCurve, PairingEngine, ProjectiveCurve};
+use snarkvm_fields::{Field, FieldParameters, One, PrimeField, Zero};
+use snarkvm_utilities::{
+    biginteger::BigInteger,
+    rand::UniformRand,
+    BitIteratorBE,
+    CanonicalSerialize,
+    ConstantSerializedSize,
+};
 
 use blake2::{digest::generic_array::GenericArray, Blake2b, Digest};
 use rand::{rngs::OsRng, thread_rng, CryptoRng, Rng, SeedableRng};
@@ -60,6 +66,36 @@ pub fn batch_mul<C: AffineCurve>(bases: &mut [C], coeff: &C::ScalarField) -> Res
     Ok(())
 }
 
+/// Same as `batch_mul`, but `bases` is split in chunks of at least `min_len` points which are
+/// multiplied and normalized on separate threads. Fewer than `min_len` points are multiplied
+/// serially, since the overhead of spawning the tasks would dominate.
+pub fn batch_mul_parallel<C: AffineCurve>(bases: &mut [C], coeff: &C::ScalarField, min_len: usize) -> Result<()> {
+    if bases.len() < min_len {
+        return batch_mul_serial(bases, coeff);
+    }
+
+    #[cfg(feature = "parallel")]
+    let chunks = {
+        let num_threads = rayon::current_num_threads();
+        let chunk_size = min_len.max((bases.len() + num_threads - 1) / num_threads);
+        bases.par_chunks_mut(chunk_size.max(1))
+    };
+    #[cfg(not(feature = "parallel"))]
+    let mut chunks = bases.chunks_mut(bases.len().max(1));
+
+    chunks.try_for_each(|chunk| batch_mul_serial(chunk, coeff))
+}
+
+fn batch_mul_serial<C: AffineCurve>(bases: &mut [C], coeff: &C::ScalarField) -> Result<()> {
+    let mut points: Vec<_> = bases.iter().map(|base| base.into_projective().mul(*coeff)).collect();
+    C::Projective::batch_normalization(points.as_mut_slice());
+    for (base, proj) in bases.iter_mut().zip(points) {
+        *base = proj.into_affine();
+    }
+
+    Ok(())
+}
+
 /// Exponentiate a large number of points, with an optional coefficient to be applied to the
 /// exponent.
 pub fn batch_exp<C: AffineCurve>(
@@ -171,6 +207,11 @@ pub fn log_2(x: usize) -> usize {
     num_bits::<usize>() - (x.leading_zeros() as usize) - 1
 }
 
+/// The length of the hashes of the setup, e.g. phase 2's `cs_hash` and contribution
+/// hashes. They are BLAKE2b hashes (see `HashWriter::into_digest`) truncated to their
+/// first `HASH_LEN` bytes, so it must be at most 64.
+pub const HASH_LEN: usize = 64;
+
 /// Abstraction over a writer which hashes the data being written.
 pub struct HashWriter<W: Write> {
     writer: W,
@@ -199,6 +240,14 @@ impl<W: Write> HashWriter<W> {
     pub fn into_hash(self) -> GenericArray<u8, U64> {
         self.hasher.finalize()
     }
+
+    /// Destroy this writer and return the first `HASH_LEN` bytes of the hash of what was written.
+    pub fn into_digest(self) -> [u8; HASH_LEN] {
+        let hash = self.into_hash();
+        let mut digest = [0u8; HASH_LEN];
+        digest.copy_from_slice(&hash[..HASH_LEN]);
+        digest
+    }
 }
 
 impl<W: Write> Write for HashWriter<W> {
@@ -254,7 +303,7 @@ pub fn from_slice(bytes: &[u8]) -> [u8; 32] {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use snarkvm_curves::bls12_377::{Bls12_377, Fr, G1Affine, G2Affine};
+    use snarkvm_curves::bls12_377::{Bls12_377, Fq, Fr, G1Affine, G1Projective, G2Affine};
 
     #[test]
     fn test_hash_to_g2() {
@@ -297,6 +346,86 @@ mod tests {
         assert!(!same_ratio::<Bls12_377>(&(g1_s, g1), &(g2, g2_s)));
     }
 
+    #[test]
+    fn test_batch_same_ratio() {
+        let rng = &mut thread_rng();
+        let g1 = G1Affine::prime_subgroup_generator();
+        let g2 = G2Affine::prime_subgroup_generator();
+
+        let mut pairs = (0..5)
+            .map(|_| {
+                let s = Fr::rand(rng);
+                ((g1, g1.mul(s)), (g2, g2.mul(s)))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(batch_same_ratio::<Bls12_377>(&pairs).unwrap(), vec![true; 5]);
+
+        // break two of the relations
+        (pairs[1].0).1 = (pairs[1].0).1.mul(Fr::rand(rng));
+        (pairs[3].1).0 = (pairs[3].1).1;
+
+        let individual = pairs
+            .iter()
+            .map(|(g1, g2)| same_ratio::<Bls12_377>(g1, g2))
+            .collect::<Vec<_>>();
+        assert_eq!(individual, vec![true, false, true, false, true]);
+        assert_eq!(batch_same_ratio::<Bls12_377>(&pairs).unwrap(), individual);
+    }
+
+    #[test]
+    fn test_check_subgroup() {
+        let rng = &mut thread_rng();
+        let mut elements = (0..1000)
+            .map(|_| G1Projective::rand(rng).into_affine())
+            .collect::<Vec<G1Affine>>();
+        check_subgroup(&elements).unwrap();
+
+        // a single point outside of the subgroup is detected wherever it is
+        let off_subgroup = (1u64..)
+            .filter_map(|x| G1Affine::from_x_coordinate(Fq::from(x), true))
+            .find(|p| !p.is_in_correct_subgroup_assuming_on_curve())
+            .unwrap();
+        for &i in &[0, 737, 999] {
+            let previous = elements[i];
+            elements[i] = off_subgroup;
+            assert!(matches!(check_subgroup(&elements), Err(Error::IncorrectSubgroup)));
+            elements[i] = previous;
+        }
+    }
+
+    #[test]
+    fn test_batch_mul_parallel() {
+        let rng = &mut thread_rng();
+        let elements = (0..1000)
+            .map(|_| G1Projective::rand(rng).into_affine())
+            .collect::<Vec<G1Affine>>();
+        let coeff = Fr::rand(rng);
+
+        let mut expected = elements.clone();
+        batch_mul(&mut expected, &coeff).unwrap();
+        for &min_len in &[0, 1, 7, 256, 1000, 1001] {
+            let mut actual = elements.clone();
+            batch_mul_parallel(&mut actual, &coeff, min_len).unwrap();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_merge_pairs_with_rng() {
+        let rng = &mut thread_rng();
+        let v1 = (0..100)
+            .map(|_| G1Projective::rand(rng).into_affine())
+            .collect::<Vec<G1Affine>>();
+        let v2 = (0..100)
+            .map(|_| G1Projective::rand(rng).into_affine())
+            .collect::<Vec<G1Affine>>();
+
+        let seed = [7u8; 32];
+        let pairs = merge_pairs_with_rng(&v1, &v2, &mut ChaChaRng::from_seed(seed));
+        assert_eq!(pairs, merge_pairs_with_rng(&v1, &v2, &mut ChaChaRng::from_seed(seed)));
+        assert!(pairs != merge_pairs_with_rng(&v1, &v2, &mut ChaChaRng::from_seed([8u8; 32])));
+    }
+
     #[test]
     fn test_power_pairs() {
         use std::ops::MulAssign;
@@ -327,8 +456,13 @@ mod tests {
 }
 
 pub fn merge_pairs<G: AffineCurve>(v1: &[G], v2: &[G]) -> (G, G) {
+    merge_pairs_with_rng(v1, v2, &mut thread_rng())
+}
+
+/// Same as `merge_pairs`, but the random linear combination is sampled from `rng`,
+/// so that it can be reproduced by seeding it.
+pub fn merge_pairs_with_rng<G: AffineCurve, R: Rng>(v1: &[G], v2: &[G], rng: &mut R) -> (G, G) {
     assert_eq!(v1.len(), v2.len());
-    let rng = &mut thread_rng();
 
     let randomness: Vec<<G::ScalarField as PrimeField>::BigInteger> =
         (0..v1.len()).map(|_| G::ScalarField::rand(rng).to_repr()).collect();
@@ -374,6 +508,67 @@ pub fn check_same_ratio<E: PairingEngine>(
     Ok(())
 }
 
+/// Same as `check_same_ratio`, but returns the target group element which both
+/// sides of the ratio evaluate to, so that the check can be logged and replayed.
+pub fn check_same_ratio_with_value<E: PairingEngine>(
+    g1: &(E::G1Affine, E::G1Affine),
+    g2: &(E::G2Affine, E::G2Affine),
+    err: &'static str,
+) -> Result<E::Fqk> {
+    let value = E::pairing(g1.0, g2.1);
+    if value != E::pairing(g1.1, g2.0) {
+        return Err(VerificationError::InvalidRatio(err).into());
+    }
+    Ok(value)
+}
+
+/// Checks that all the elements are in the prime order subgroup, by checking that
+/// multiplying them by the group's order gives the identity. The elements are
+/// checked in parallel when the `parallel` feature is enabled.
+pub fn check_subgroup<C: AffineCurve>(elements: &[C]) -> Result<()> {
+    let modulus = <<C::ScalarField as PrimeField>::Parameters as FieldParameters>::MODULUS;
+    let all_in_prime_order_subgroup = cfg_iter!(elements).all(|p| p.mul_bits(BitIteratorBE::new(modulus)).is_zero());
+    if !all_in_prime_order_subgroup {
+        return Err(Error::IncorrectSubgroup);
+    }
+    Ok(())
+}
+
+/// Checks a batch of `same_ratio` relations at once. Each relation is scaled by a
+/// random coefficient and all of them are evaluated with a single multi-Miller-loop
+/// and final exponentiation. If the batch does not pass, the relations are checked
+/// individually so that the result tells which of them are invalid.
+pub fn batch_same_ratio<E: PairingEngine>(
+    pairs: &[((E::G1Affine, E::G1Affine), (E::G2Affine, E::G2Affine))],
+) -> Result<Vec<bool>> {
+    batch_same_ratio_with_rng::<E, _>(pairs, &mut thread_rng())
+}
+
+/// Same as `batch_same_ratio`, but the random coefficients are sampled from `rng`,
+/// so that they can be reproduced by seeding it.
+pub fn batch_same_ratio_with_rng<E: PairingEngine, R: Rng>(
+    pairs: &[((E::G1Affine, E::G1Affine), (E::G2Affine, E::G2Affine))],
+    rng: &mut R,
+) -> Result<Vec<bool>> {
+    if pairs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // e(a, d) == e(b, c) <=> e(r * a, d) * e(-r * b, c) == 1
+    let mut prepared = Vec::with_capacity(2 * pairs.len());
+    for ((a, b), (c, d)) in pairs {
+        let r = E::Fr::rand(rng);
+        prepared.push((a.mul(r).prepare(), d.prepare()));
+        prepared.push((b.mul(-r).prepare(), c.prepare()));
+    }
+
+    if E::product_of_pairings(prepared.iter()).is_one() {
+        return Ok(vec![true; pairs.len()]);
+    }
+
+    Ok(pairs.iter().map(|(g1, g2)| same_ratio::<E>(g1, g2)).collect())
+}
+
 /// Compute BLAKE2b(personalization | transcript | g^s | g^{s*x})
 /// and then hash it to G2
 pub fn compute_g2_s<E: PairingEngine>(
@@ -11,6 +11,9 @@ pub type Result<T> = std::result::Result<T, Error>;
 mod groth16_utils;
 pub use groth16_utils::Groth16Params;
 
+mod backend;
+pub use backend::{CpuBackend, ScalarMulBackend};
+
 mod elements;
 pub use elements::{CheckForCorrectness, ElementType, UseCompression};
 
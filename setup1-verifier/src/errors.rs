@@ -69,3 +69,9 @@ impl From<snarkvm_dpc::AccountError> for VerifierError {
         VerifierError::Crate("snarkvm_dpc", format!("{:?}", error))
     }
 }
+
+impl From<phase1_cli::Phase1CliError> for VerifierError {
+    fn from(error: phase1_cli::Phase1CliError) -> Self {
+        VerifierError::Crate("phase1_cli", format!("{:?}", error))
+    }
+}
@@ -193,7 +193,7 @@ impl Verifier {
         challenge_file_locator: &str,
         response_locator: &str,
         next_challenge_locator: &str,
-    ) -> u128 {
+    ) -> Result<u128, VerifierError> {
         // Create the parent directory for the `next_challenge_locator` if it doesn't already exist.
         create_parent_directory(&next_challenge_locator);
         // Remove the `next_challenge_locator` if it already exists.
@@ -224,9 +224,9 @@ impl Verifier {
                 &next_challenge_locator,
                 &phase1_chunked_parameters!(BW6_761, settings, chunk_id),
             ),
-        };
+        }?;
 
-        start.elapsed().as_millis()
+        Ok(start.elapsed().as_millis())
     }
 
     ///
@@ -377,7 +377,8 @@ impl Verifier {
             chunk_id, contribution_id
         );
 
-        let duration = self.run_verification(chunk_id, &challenge_locator, &response_locator, &next_challenge_locator);
+        let duration =
+            self.run_verification(chunk_id, &challenge_locator, &response_locator, &next_challenge_locator)?;
         info!(
             "Verification on chunk {} contribution {} completed in {} ms",
             chunk_id, contribution_id, duration,